@@ -1,75 +1,1109 @@
 use std::{
-    io::Cursor,
-    sync::mpsc::{channel, Receiver, Sender},
+    fs::{self, File},
+    io::{self, BufReader, Cursor},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
+use anyhow::Result;
 use espeaker::SpeakerSource;
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Source};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use ultraviolet::Vec2;
+
+use crate::config;
+
+/// A sound decoded by [`AudioEngine::load`], cheap to copy around and play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SoundId(usize);
+
+/// Every sound plays through one of these, each with its own volume on top of the master volume.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bus {
+    Sfx,
+    Music,
+    Voice,
+}
+
+impl Bus {
+    pub const ALL: &'static [Self] = &[Self::Sfx, Self::Music, Self::Voice];
+
+    /// How the bus is written in a volumes file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sfx => "Sfx",
+            Self::Music => "Music",
+            Self::Voice => "Voice",
+        }
+    }
+}
+
+/// How often a loaded sound may play at once, set with [`AudioEngine::set_limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SoundLimits {
+    /// Voices of this sound playing at once, the oldest is cut off to make room for a new one.
+    pub max_voices: usize,
+    /// When every voice is taken, a sound only cuts off one of the same or lower priority.
+    pub priority: u8,
+    /// Plays closer together than this are dropped, so a burst of hits in one frame sounds once.
+    pub min_interval: Duration,
+}
+
+impl Default for SoundLimits {
+    fn default() -> Self {
+        Self {
+            max_voices: usize::MAX,
+            priority: 0,
+            min_interval: Duration::ZERO,
+        }
+    }
+}
+
+enum AudioCommand {
+    Play(Vec<u8>, Bus),
+    PlaySound(SoundId, Bus),
+    PlaySoundAt(SoundId, Vec2, Bus),
+    PlaySpeaker(SpeakerSource, Bus),
+    PlayMusic(Vec<PathBuf>, Duration),
+    QueueMusic(PathBuf),
+    StopMusic(Duration),
+}
 
 pub struct AudioEngine {
-    _os: OutputStream,
-    handle: OutputStreamHandle,
-    rx: Receiver<Vec<u8>>,
-    tx: Sender<Vec<u8>>,
-    rxs: Receiver<SpeakerSource>,
-    txs: Sender<SpeakerSource>,
+    // `None` when there is no output device, sounds are then dropped
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    sounds: Vec<Sound>,
+    voices: Voices,
+    mixer: Arc<Mixer>,
+    music: Music,
+    rx: Receiver<AudioCommand>,
+    tx: Sender<AudioCommand>,
 }
 
 impl AudioEngine {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        let (_os, handle) =
-            OutputStream::try_default().expect("Failed to initialize audio output stream");
+        let output = match OutputStream::try_default() {
+            Ok(output) => Some(output),
+            Err(err) => {
+                log::error!("Failed to initialize audio output stream: {}", err);
+                None
+            }
+        };
+        Self::with_output(output)
+    }
+
+    /// Drops every sound without opening an audio device, e.g. for tests.
+    pub fn silent() -> Self {
+        Self::with_output(None)
+    }
+
+    fn with_output(output: Option<(OutputStream, OutputStreamHandle)>) -> Self {
         let (tx, rx) = channel();
-        let (txs, rxs) = channel();
         Self {
-            _os,
-            handle,
+            output,
+            sounds: vec![],
+            voices: Voices::new(DEFAULT_MAX_VOICES),
+            mixer: Arc::default(),
+            music: Music::default(),
             rx,
             tx,
-            rxs,
-            txs,
         }
     }
 
     pub fn player(&self) -> AudioPlayer {
         AudioPlayer {
             tx: self.tx.clone(),
-            txs: self.txs.clone(),
+            mixer: self.mixer.clone(),
+        }
+    }
+
+    /// Decodes `data` once up front, so playing it later only sends the id.
+    pub fn load(&mut self, data: &[u8]) -> Result<SoundId> {
+        let decoder = Decoder::new(Cursor::new(data.to_vec()))?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        self.sounds.push(Sound {
+            samples: decoder.convert_samples().collect(),
+            channels,
+            sample_rate,
+            limits: SoundLimits::default(),
+            last_played: None,
+        });
+        Ok(SoundId(self.sounds.len() - 1))
+    }
+
+    pub fn set_limits(&mut self, sound: SoundId, limits: SoundLimits) {
+        match self.sounds.get_mut(sound.0) {
+            Some(sound) => sound.limits = limits,
+            None => log::warn!("Tried to limit unknown sound {:?}", sound),
         }
     }
 
+    /// Loaded sounds playing at once across all of them, 32 by default.
+    pub fn set_max_voices(&mut self, max: usize) {
+        self.voices.max = max;
+    }
+
     pub fn update(&mut self) {
-        while let Ok(data) = self.rx.try_recv() {
-            match Decoder::new(Cursor::new(data)) {
-                Ok(decoder) => {
-                    self.handle.play_raw(decoder.convert_samples()).unwrap();
+        let Some((_, handle)) = &self.output else {
+            self.rx.try_iter().for_each(drop);
+            return;
+        };
+        while let Ok(command) = self.rx.try_recv() {
+            match command {
+                AudioCommand::Play(data, bus) => match Decoder::new(Cursor::new(data)) {
+                    Ok(decoder) => {
+                        handle
+                            .play_raw(BusSource::new(decoder.convert_samples(), bus, &self.mixer))
+                            .unwrap();
+                    }
+                    Err(err) => log::warn!("Error decoding audio: {}", err),
+                },
+                AudioCommand::PlaySound(id, bus) => {
+                    let Some(sound) = self.sounds.get_mut(id.0) else {
+                        log::warn!("Tried to play unknown sound {:?}", id);
+                        continue;
+                    };
+                    let Some(voice) = self.voices.start(id, sound) else {
+                        continue;
+                    };
+                    handle
+                        .play_raw(BusSource::new(
+                            VoiceSource::new(sound.source(), voice),
+                            bus,
+                            &self.mixer,
+                        ))
+                        .unwrap();
+                }
+                AudioCommand::PlaySoundAt(id, pos, bus) => {
+                    let Some(sound) = self.sounds.get_mut(id.0) else {
+                        log::warn!("Tried to play unknown sound {:?}", id);
+                        continue;
+                    };
+                    // out of earshot doesn't take up a voice
+                    let Some(gains) = self.mixer.listener.gains(pos) else {
+                        continue;
+                    };
+                    let Some(voice) = self.voices.start(id, sound) else {
+                        continue;
+                    };
+                    handle
+                        .play_raw(BusSource::new(
+                            VoiceSource::new(SpatialSource::new(sound.source(), gains), voice),
+                            bus,
+                            &self.mixer,
+                        ))
+                        .unwrap();
+                }
+                AudioCommand::PlaySpeaker(source, bus) => {
+                    handle
+                        .play_raw(BusSource::new(source.convert_samples(), bus, &self.mixer))
+                        .unwrap();
                 }
-                Err(err) => println!("Error decoding audio: {}", err),
+                AudioCommand::PlayMusic(playlist, fade) => self.music.play(handle, playlist, fade),
+                AudioCommand::QueueMusic(track) => self.music.queue(handle, track),
+                AudioCommand::StopMusic(fade) => self.music.stop(fade),
             }
         }
-        while let Ok(source) = self.rxs.try_recv() {
-            self.handle.play_raw(source.convert_samples()).unwrap();
-        }
+        self.music.update(&self.mixer);
     }
 }
 
+/// Plays sounds and sets volumes from anywhere, volume changes apply to what is already playing.
 #[derive(Clone, Debug)]
 pub struct AudioPlayer {
-    tx: Sender<Vec<u8>>,
-    txs: Sender<SpeakerSource>,
+    tx: Sender<AudioCommand>,
+    mixer: Arc<Mixer>,
 }
 
 impl AudioPlayer {
+    /// Decodes `data` on every call, prefer [`AudioPlayer::play_sound`] for anything played often.
     pub fn play(&self, data: Vec<u8>) {
-        if let Err(err) = self.tx.send(data) {
-            eprintln!("Failed to send audio data: {}", err);
-        }
+        self.play_on(Bus::Sfx, data);
+    }
+
+    pub fn play_on(&self, bus: Bus, data: Vec<u8>) {
+        self.send(AudioCommand::Play(data, bus));
+    }
+
+    pub fn play_sound(&self, sound: SoundId) {
+        self.play_sound_on(Bus::Sfx, sound);
     }
 
+    pub fn play_sound_on(&self, bus: Bus, sound: SoundId) {
+        self.send(AudioCommand::PlaySound(sound, bus));
+    }
+
+    /// Plays `sound` panned and attenuated by where `pos` is relative to the listener, see
+    /// [`AudioPlayer::set_listener`].
+    pub fn play_sound_at(&self, sound: SoundId, pos: Vec2) {
+        self.play_sound_at_on(Bus::Sfx, sound, pos);
+    }
+
+    pub fn play_sound_at_on(&self, bus: Bus, sound: SoundId, pos: Vec2) {
+        self.send(AudioCommand::PlaySoundAt(sound, pos, bus));
+    }
+
+    /// Where positional sounds are heard from in world space, usually the camera position.
+    pub fn set_listener(&self, pos: Vec2) {
+        self.mixer.listener.set_pos(pos);
+    }
+
+    /// How far away from the listener positional sounds fade out to silence, 2000 by default and
+    /// at least 1.
+    pub fn set_hearing_range(&self, range: f32) {
+        self.mixer.listener.set_range(range);
+    }
+
+    /// Speech goes to [`Bus::Voice`].
     pub fn play_speaker(&self, source: SpeakerSource) {
-        if let Err(err) = self.txs.send(source) {
-            eprintln!("Failed to send speaker source: {}", err);
+        self.send(AudioCommand::PlaySpeaker(source, Bus::Voice));
+    }
+
+    /// Streams `playlist` from disk on [`Bus::Music`], over and over, crossfading from whatever
+    /// played before over `fade`. A single track just loops, missing tracks are skipped.
+    pub fn play_music(&self, playlist: Vec<PathBuf>, fade: Duration) {
+        self.send(AudioCommand::PlayMusic(playlist, fade));
+    }
+
+    /// Adds a track to the end of the current playlist.
+    pub fn queue_music(&self, track: impl Into<PathBuf>) {
+        self.send(AudioCommand::QueueMusic(track.into()));
+    }
+
+    pub fn stop_music(&self, fade: Duration) {
+        self.send(AudioCommand::StopMusic(fade));
+    }
+
+    /// From 0 for silent to 1 for as loud as the sounds are, higher amplifies.
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.mixer.bus(bus).volume()
+    }
+
+    pub fn set_volume(&self, bus: Bus, volume: f32) {
+        self.mixer.bus(bus).set_volume(volume);
+    }
+
+    pub fn muted(&self, bus: Bus) -> bool {
+        self.mixer.bus(bus).muted()
+    }
+
+    pub fn set_muted(&self, bus: Bus, muted: bool) {
+        self.mixer.bus(bus).set_muted(muted);
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.mixer.master.volume()
+    }
+
+    pub fn set_master_volume(&self, volume: f32) {
+        self.mixer.master.set_volume(volume);
+    }
+
+    pub fn master_muted(&self) -> bool {
+        self.mixer.master.muted()
+    }
+
+    pub fn set_master_muted(&self, muted: bool) {
+        self.mixer.master.set_muted(muted);
+    }
+
+    /// Reads the volumes from `path`, one `Bus = volume` per line with `muted` after the volume
+    /// to mute the bus, and `Master` for the master volume. A missing file is created from the
+    /// current volumes so players have something to edit.
+    pub fn load_volumes(&self, path: impl AsRef<Path>) {
+        if let Some(text) = config::load_or_create(path, || self.volumes_config()) {
+            self.apply_volumes(&text);
+        }
+    }
+
+    pub fn save_volumes(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.volumes_config())
+    }
+
+    fn gains(&self) -> impl Iterator<Item = (&'static str, &Gain)> {
+        std::iter::once(("Master", &self.mixer.master)).chain(
+            Bus::ALL
+                .iter()
+                .map(|&bus| (bus.name(), self.mixer.bus(bus))),
+        )
+    }
+
+    fn apply_volumes(&self, text: &str) {
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                log::warn!("Ignoring volume line without `=`: {}", line);
+                continue;
+            };
+            let Some((_, gain)) = self.gains().find(|(n, _)| *n == name.trim()) else {
+                log::warn!("Ignoring volume for unknown bus {}", name.trim());
+                continue;
+            };
+            let mut muted = false;
+            for word in value.split_whitespace() {
+                match word {
+                    "muted" => muted = true,
+                    word => match word.parse() {
+                        Ok(volume) => gain.set_volume(volume),
+                        Err(_) => log::warn!("Ignoring unknown volume {}", word),
+                    },
+                }
+            }
+            gain.set_muted(muted);
+        }
+    }
+
+    fn volumes_config(&self) -> String {
+        self.gains()
+            .map(|(name, gain)| {
+                let muted = if gain.muted() { " muted" } else { "" };
+                format!("{} = {}{}\n", name, gain.volume(), muted)
+            })
+            .collect()
+    }
+
+    fn send(&self, command: AudioCommand) {
+        if let Err(err) = self.tx.send(command) {
+            log::warn!("Failed to send audio command: {}", err);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Music {
+    playlist: Vec<PathBuf>,
+    // the track to queue after the ones already in `playing`
+    next: usize,
+    playing: Option<MusicSink>,
+    fading_out: Vec<MusicSink>,
+}
+
+impl Music {
+    fn play(&mut self, handle: &OutputStreamHandle, playlist: Vec<PathBuf>, fade: Duration) {
+        match Sink::try_new(handle) {
+            Ok(sink) => self.start(sink, playlist, fade),
+            Err(err) => {
+                self.stop(fade);
+                log::error!("Failed to create music sink: {}", err);
+            }
+        }
+    }
+
+    // also takes sinks without an output device, like `Sink::new_idle`
+    fn start(&mut self, sink: Sink, playlist: Vec<PathBuf>, fade: Duration) {
+        self.stop(fade);
+        self.playlist = playlist;
+        self.next = 0;
+        self.playing = Some(MusicSink::new(sink, 0., 1., fade));
+    }
+
+    fn queue(&mut self, handle: &OutputStreamHandle, track: PathBuf) {
+        if self.playing.is_some() {
+            self.playlist.push(track);
+        } else {
+            self.play(handle, vec![track], Duration::ZERO);
+        }
+    }
+
+    fn stop(&mut self, fade: Duration) {
+        if let Some(playing) = self.playing.take() {
+            let volume = playing.volume(Instant::now());
+            self.fading_out
+                .push(MusicSink::new(playing.sink, volume, 0., fade));
+        }
+        self.playlist.clear();
+    }
+
+    fn update(&mut self, mixer: &Arc<Mixer>) {
+        let now = Instant::now();
+        self.fading_out.retain(|music| {
+            music.sink.set_volume(music.volume(now));
+            !music.faded(now)
+        });
+        let Some(playing) = &self.playing else {
+            return;
+        };
+        playing.sink.set_volume(playing.volume(now));
+        // always one track queued behind the current one so they play back to back
+        while playing.sink.len() < 2 && !self.playlist.is_empty() {
+            self.next %= self.playlist.len();
+            let path = &self.playlist[self.next];
+            let file = match File::open(path) {
+                Ok(file) => file,
+                // music is optional, a missing track isn't worth a warning
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    log::debug!("No music at {}", path.display());
+                    self.playlist.remove(self.next);
+                    continue;
+                }
+                Err(err) => {
+                    log::warn!("Failed to play music {}: {}", path.display(), err);
+                    self.playlist.remove(self.next);
+                    continue;
+                }
+            };
+            match Decoder::new(BufReader::new(file)) {
+                Ok(decoder) => {
+                    playing.sink.append(BusSource::new(
+                        decoder.convert_samples(),
+                        Bus::Music,
+                        mixer,
+                    ));
+                    self.next += 1;
+                }
+                Err(err) => {
+                    log::warn!("Failed to play music {}: {}", path.display(), err);
+                    self.playlist.remove(self.next);
+                }
+            }
+        }
+    }
+}
+
+// a sink ramping its volume from `from` to `to` over `fade`
+struct MusicSink {
+    sink: Sink,
+    from: f32,
+    to: f32,
+    started: Instant,
+    fade: Duration,
+}
+
+impl MusicSink {
+    fn new(sink: Sink, from: f32, to: f32, fade: Duration) -> Self {
+        sink.set_volume(from);
+        Self {
+            sink,
+            from,
+            to,
+            started: Instant::now(),
+            fade,
+        }
+    }
+
+    fn progress(&self, now: Instant) -> f32 {
+        if self.fade.is_zero() {
+            1.
+        } else {
+            (now.saturating_duration_since(self.started).as_secs_f32() / self.fade.as_secs_f32())
+                .min(1.)
         }
     }
+
+    fn volume(&self, now: Instant) -> f32 {
+        self.from + (self.to - self.from) * self.progress(now)
+    }
+
+    fn faded(&self, now: Instant) -> bool {
+        self.progress(now) >= 1.
+    }
+}
+
+// shared with the audio thread through `BusSource`, hence the atomics
+#[derive(Debug, Default)]
+struct Mixer {
+    master: Gain,
+    buses: [Gain; 3],
+    listener: Listener,
+}
+
+impl Mixer {
+    fn bus(&self, bus: Bus) -> &Gain {
+        &self.buses[bus as usize]
+    }
+
+    fn gain(&self, bus: Bus) -> f32 {
+        self.master.gain() * self.bus(bus).gain()
+    }
+}
+
+#[derive(Debug)]
+struct Gain {
+    // an f32's bits, there is no atomic float
+    volume: AtomicU32,
+    muted: AtomicBool,
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self {
+            volume: AtomicU32::new(1f32.to_bits()),
+            muted: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Gain {
+    fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Relaxed))
+    }
+
+    fn set_volume(&self, volume: f32) {
+        self.volume
+            .store(volume.max(0.).to_bits(), Ordering::Relaxed);
+    }
+
+    fn muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed);
+    }
+
+    fn gain(&self) -> f32 {
+        if self.muted() {
+            0.
+        } else {
+            self.volume()
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Listener {
+    x: AtomicU32,
+    y: AtomicU32,
+    range: AtomicU32,
+}
+
+impl Default for Listener {
+    fn default() -> Self {
+        Self {
+            x: AtomicU32::new(0f32.to_bits()),
+            y: AtomicU32::new(0f32.to_bits()),
+            range: AtomicU32::new(2000f32.to_bits()),
+        }
+    }
+}
+
+impl Listener {
+    fn set_pos(&self, pos: Vec2) {
+        self.x.store(pos.x.to_bits(), Ordering::Relaxed);
+        self.y.store(pos.y.to_bits(), Ordering::Relaxed);
+    }
+
+    fn set_range(&self, range: f32) {
+        // `gains` divides by it, and a negative range would flip left and right
+        self.range.store(range.max(1.).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Left and right gains for a sound at `pos`, `None` when it is out of earshot.
+    fn gains(&self, pos: Vec2) -> Option<[f32; 2]> {
+        let load = |v: &AtomicU32| f32::from_bits(v.load(Ordering::Relaxed));
+        let offset = pos - Vec2::new(load(&self.x), load(&self.y));
+        let range = load(&self.range);
+        let attenuation = 1. - offset.mag() / range;
+        if attenuation <= 0. {
+            return None;
+        }
+        // constant power panning, so sounds don't get quieter as they cross the middle
+        let pan = (offset.x / range).clamp(-1., 1.);
+        let angle = (pan + 1.) * std::f32::consts::FRAC_PI_4;
+        Some([
+            angle.cos() * attenuation * attenuation,
+            angle.sin() * attenuation * attenuation,
+        ])
+    }
+}
+
+// plays mono and stereo sources as stereo with separate left and right gains
+struct SpatialSource<S> {
+    inner: S,
+    gains: [f32; 2],
+    channel: u16,
+    // the right half of a mono sample
+    pending: Option<f32>,
+}
+
+impl<S: Source<Item = f32>> SpatialSource<S> {
+    fn new(inner: S, gains: [f32; 2]) -> Self {
+        Self {
+            inner,
+            gains,
+            channel: 0,
+            pending: None,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for SpatialSource<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(sample) = self.pending.take() {
+            return Some(sample * self.gains[1]);
+        }
+        let sample = self.inner.next()?;
+        if self.inner.channels() == 1 {
+            self.pending = Some(sample);
+            return Some(sample * self.gains[0]);
+        }
+        let gain = self.gains[self.channel as usize % 2];
+        self.channel = (self.channel + 1) % self.inner.channels();
+        Some(sample * gain)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for SpatialSource<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        let len = self.inner.current_frame_len()?;
+        Some(if self.inner.channels() == 1 {
+            len * 2 + self.pending.is_some() as usize
+        } else {
+            len
+        })
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels().max(2)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+// scales `inner` by its bus' current gain
+struct BusSource<S> {
+    inner: S,
+    bus: Bus,
+    mixer: Arc<Mixer>,
+}
+
+impl<S> BusSource<S> {
+    fn new(inner: S, bus: Bus, mixer: &Arc<Mixer>) -> Self {
+        Self {
+            inner,
+            bus,
+            mixer: mixer.clone(),
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for BusSource<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.inner.next().map(|s| s * self.mixer.gain(self.bus))
+    }
+}
+
+impl<S: Source<Item = f32>> Source for BusSource<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+const DEFAULT_MAX_VOICES: usize = 32;
+// long enough for a cut off voice not to click, short enough not to be heard over the new one
+const STEAL_FADE: Duration = Duration::from_millis(5);
+
+// the loaded sounds currently playing, to cut off the oldest when there are too many
+struct Voices {
+    playing: Vec<Voice>,
+    max: usize,
+    // voices started so far, what their age is told by
+    started: u64,
+}
+
+struct Voice {
+    sound: SoundId,
+    priority: u8,
+    started: u64,
+    // cleared by the source once it runs out, or by us to cut it off
+    alive: Arc<AtomicBool>,
+}
+
+impl Voices {
+    fn new(max: usize) -> Self {
+        Self {
+            playing: vec![],
+            max,
+            started: 0,
+        }
+    }
+
+    /// Makes room for a new voice of `sound`, `None` if it shouldn't play at all.
+    fn start(&mut self, id: SoundId, sound: &mut Sound) -> Option<Arc<AtomicBool>> {
+        let now = Instant::now();
+        let limits = sound.limits;
+        if sound
+            .last_played
+            .is_some_and(|last| now.duration_since(last) < limits.min_interval)
+        {
+            return None;
+        }
+        self.playing.retain(|v| v.alive.load(Ordering::Relaxed));
+
+        if self.playing.iter().filter(|v| v.sound == id).count() >= limits.max_voices {
+            self.steal(|v| v.sound == id)?;
+        }
+        if self.playing.len() >= self.max {
+            self.steal(|v| v.priority <= limits.priority)?;
+        }
+
+        sound.last_played = Some(now);
+        let alive = Arc::new(AtomicBool::new(true));
+        self.started += 1;
+        self.playing.push(Voice {
+            sound: id,
+            priority: limits.priority,
+            started: self.started,
+            alive: alive.clone(),
+        });
+        Some(alive)
+    }
+
+    // cuts off the lowest priority, oldest voice out of those matching `can_steal`
+    fn steal(&mut self, can_steal: impl Fn(&Voice) -> bool) -> Option<()> {
+        let (i, _) = self
+            .playing
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| can_steal(v))
+            .min_by_key(|(_, v)| (v.priority, v.started))?;
+        self.playing
+            .swap_remove(i)
+            .alive
+            .store(false, Ordering::Relaxed);
+        Some(())
+    }
+}
+
+// fades `inner` out over `STEAL_FADE` once `alive` is cleared, and clears it when `inner` runs out
+struct VoiceSource<S> {
+    inner: S,
+    alive: Arc<AtomicBool>,
+    // samples left of the fade out, once it started
+    fading: Option<u32>,
+    fade_len: u32,
+}
+
+impl<S: Source<Item = f32>> VoiceSource<S> {
+    fn new(inner: S, alive: Arc<AtomicBool>) -> Self {
+        let samples_per_sec = inner.sample_rate() as f32 * inner.channels() as f32;
+        Self {
+            fade_len: (STEAL_FADE.as_secs_f32() * samples_per_sec).max(1.) as u32,
+            inner,
+            alive,
+            fading: None,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for VoiceSource<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.fading.is_none() && !self.alive.load(Ordering::Relaxed) {
+            self.fading = Some(self.fade_len);
+        }
+        let gain = match &mut self.fading {
+            Some(0) => return None,
+            Some(left) => {
+                *left -= 1;
+                *left as f32 / self.fade_len as f32
+            }
+            None => 1.,
+        };
+        let sample = self.inner.next();
+        if sample.is_none() {
+            self.alive.store(false, Ordering::Relaxed);
+        }
+        sample.map(|s| s * gain)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for VoiceSource<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+// decoded samples, shared between every voice playing them
+struct Sound {
+    samples: Arc<[f32]>,
+    channels: u16,
+    sample_rate: u32,
+    limits: SoundLimits,
+    last_played: Option<Instant>,
+}
+
+impl Sound {
+    fn source(&self) -> SoundSource {
+        SoundSource {
+            samples: self.samples.clone(),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            pos: 0,
+        }
+    }
+}
+
+struct SoundSource {
+    samples: Arc<[f32]>,
+    channels: u16,
+    sample_rate: u32,
+    pos: usize,
+}
+
+impl Iterator for SoundSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.pos).copied();
+        self.pos += 1;
+        sample
+    }
+}
+
+impl Source for SoundSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.pos))
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(
+            self.samples.len() as f64 / self.channels as f64 / self.sample_rate as f64,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volumes_round_trip() {
+        let player = AudioEngine::silent().player();
+        player.set_master_volume(0.5);
+        player.set_volume(Bus::Music, 0.25);
+        player.set_muted(Bus::Voice, true);
+
+        let loaded = AudioEngine::silent().player();
+        loaded.apply_volumes(&player.volumes_config());
+        assert_eq!(loaded.master_volume(), 0.5);
+        assert!(!loaded.master_muted());
+        assert_eq!(loaded.volume(Bus::Music), 0.25);
+        assert_eq!(loaded.volume(Bus::Sfx), 1.);
+        assert!(loaded.muted(Bus::Voice));
+        assert!(!loaded.muted(Bus::Music));
+    }
+
+    #[test]
+    fn hearing_range_stays_positive() {
+        let listener = Listener::default();
+        for range in [0., -100.] {
+            listener.set_range(range);
+            let [left, right] = listener.gains(Vec2::zero()).unwrap();
+            assert!(left.is_finite() && right.is_finite());
+            assert_eq!(
+                listener.gains(Vec2::new(0.5, 0.)).map(|[l, r]| l < r),
+                Some(true)
+            );
+        }
+    }
+
+    fn sound(limits: SoundLimits) -> Sound {
+        Sound {
+            samples: vec![1.; 1000].into(),
+            channels: 1,
+            sample_rate: 1000,
+            limits,
+            last_played: None,
+        }
+    }
+
+    fn alive(voice: &Arc<AtomicBool>) -> bool {
+        voice.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn per_sound_cap_steals_the_oldest() {
+        let mut voices = Voices::new(DEFAULT_MAX_VOICES);
+        let mut hit = sound(SoundLimits {
+            max_voices: 2,
+            ..Default::default()
+        });
+        let first = voices.start(SoundId(0), &mut hit).unwrap();
+        let second = voices.start(SoundId(0), &mut hit).unwrap();
+        let third = voices.start(SoundId(0), &mut hit).unwrap();
+        assert!(!alive(&first));
+        assert!(alive(&second) && alive(&third));
+
+        // other sounds don't count towards it
+        let mut other = sound(SoundLimits::default());
+        voices.start(SoundId(1), &mut other).unwrap();
+        assert!(alive(&second) && alive(&third));
+    }
+
+    #[test]
+    fn global_cap_respects_priority() {
+        let mut voices = Voices::new(2);
+        let mut low = sound(SoundLimits::default());
+        let mut high = sound(SoundLimits {
+            priority: 1,
+            ..Default::default()
+        });
+        let low_1 = voices.start(SoundId(0), &mut low).unwrap();
+        let low_2 = voices.start(SoundId(0), &mut low).unwrap();
+
+        let high_1 = voices.start(SoundId(1), &mut high).unwrap();
+        assert!(!alive(&low_1) && alive(&low_2));
+        let high_2 = voices.start(SoundId(1), &mut high).unwrap();
+        assert!(!alive(&low_2));
+
+        // nothing of the same or lower priority is left to cut off
+        assert!(voices.start(SoundId(0), &mut low).is_none());
+        assert!(alive(&high_1) && alive(&high_2));
+
+        // the oldest of the same priority goes first
+        voices.start(SoundId(1), &mut high).unwrap();
+        assert!(!alive(&high_1) && alive(&high_2));
+    }
+
+    #[test]
+    fn min_interval_drops_retriggers() {
+        let mut voices = Voices::new(DEFAULT_MAX_VOICES);
+        let mut hit = sound(SoundLimits {
+            min_interval: Duration::from_secs(60),
+            ..Default::default()
+        });
+        assert!(voices.start(SoundId(0), &mut hit).is_some());
+        assert!(voices.start(SoundId(0), &mut hit).is_none());
+        assert_eq!(voices.playing.len(), 1);
+    }
+
+    #[test]
+    fn stolen_voices_fade_out() {
+        let alive = Arc::new(AtomicBool::new(true));
+        let mut source = VoiceSource::new(sound(SoundLimits::default()).source(), alive.clone());
+        assert_eq!(source.next(), Some(1.));
+
+        alive.store(false, Ordering::Relaxed);
+        // 5 ms at 1000 samples a second
+        assert_eq!(
+            source.by_ref().collect::<Vec<_>>(),
+            [0.8, 0.6, 0.4, 0.2, 0.]
+        );
+    }
+
+    // `len` mono samples of `value` at 1000 Hz
+    fn track(name: &str, value: i16, len: u32) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("engine-{}-{}.wav", std::process::id(), name));
+        let mut wav = Vec::new();
+        wav.extend(b"RIFF");
+        wav.extend((36 + len * 2).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes()); // pcm
+        wav.extend(1u16.to_le_bytes()); // channels
+        wav.extend(1000u32.to_le_bytes()); // sample rate
+        wav.extend(2000u32.to_le_bytes()); // bytes per second
+        wav.extend(2u16.to_le_bytes()); // bytes per frame
+        wav.extend(16u16.to_le_bytes()); // bits per sample
+        wav.extend(b"data");
+        wav.extend((len * 2).to_le_bytes());
+        for _ in 0..len {
+            wav.extend(value.to_le_bytes());
+        }
+        fs::write(&path, wav).unwrap();
+        path
+    }
+
+    #[test]
+    fn music_loops_the_playlist_in_order() {
+        let first = track("first", i16::MAX / 4, 10);
+        let second = track("second", i16::MAX / 2, 10);
+        let missing = std::env::temp_dir().join("engine-missing-track.wav");
+        let mixer = Arc::default();
+        let (sink, mut output) = Sink::new_idle();
+
+        let mut music = Music::default();
+        music.start(
+            sink,
+            vec![first.clone(), missing, second.clone()],
+            Duration::ZERO,
+        );
+        music.update(&mixer);
+        // missing tracks are dropped, the rest stays queued two at a time
+        assert_eq!(music.playlist, [first.clone(), second.clone()]);
+        assert_eq!(music.playing.as_ref().unwrap().sink.len(), 2);
+
+        let mut heard = vec![];
+        for _ in 0..3 {
+            heard.extend(output.by_ref().take(10));
+            music.update(&mixer);
+            assert_eq!(music.playing.as_ref().unwrap().sink.len(), 2);
+        }
+        let expected = [0.25, 0.5, 0.25].map(|value| [value; 10]).concat();
+        assert_eq!(heard.len(), expected.len());
+        assert!(heard
+            .iter()
+            .zip(&expected)
+            .all(|(heard, expected)| (heard - expected).abs() < 0.001));
+
+        fs::remove_file(first).unwrap();
+        fs::remove_file(second).unwrap();
+    }
+
+    #[test]
+    fn music_fades_linearly() {
+        let fading = MusicSink::new(Sink::new_idle().0, 1., 0.2, Duration::from_secs(2));
+        let volume_at = |secs| fading.volume(fading.started + Duration::from_secs_f32(secs));
+        for (secs, volume) in [(0., 1.), (1., 0.6), (2., 0.2), (10., 0.2)] {
+            assert!((volume_at(secs) - volume).abs() < 1e-6, "{} s", secs);
+        }
+        assert!(!fading.faded(fading.started + Duration::from_secs(1)));
+        assert!(fading.faded(fading.started + Duration::from_secs(2)));
+
+        let cut = MusicSink::new(Sink::new_idle().0, 1., 0., Duration::ZERO);
+        assert_eq!(cut.volume(cut.started), 0.);
+        assert!(cut.faded(cut.started));
+    }
 }
@@ -0,0 +1,40 @@
+use ultraviolet::Vec2;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+/// Where the world is on screen, the same mapping `game.vert` does.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub pos: Vec2,
+    pub scale: f32,
+    /// The window's inner size.
+    pub viewport: PhysicalSize<u32>,
+}
+
+impl Camera {
+    pub fn screen_to_world(&self, screen: PhysicalPosition<f64>) -> Vec2 {
+        Vec2::new(
+            self.pos.x + (2. * screen.x as f32 - self.viewport.width as f32) / self.scale,
+            self.pos.y + (2. * screen.y as f32 - self.viewport.height as f32) / self.scale,
+        )
+    }
+
+    pub fn world_to_screen(&self, world: Vec2) -> PhysicalPosition<f64> {
+        let offset = (world - self.pos) * self.scale;
+        PhysicalPosition::new(
+            ((offset.x + self.viewport.width as f32) / 2.) as f64,
+            ((offset.y + self.viewport.height as f32) / 2.) as f64,
+        )
+    }
+}
+
+/// Turns a window position into a UI offset from `anchor`, the inverse of what `ui.vert` does.
+pub fn screen_to_ui(
+    screen: PhysicalPosition<f64>,
+    viewport: PhysicalSize<u32>,
+    anchor: Vec2,
+) -> Vec2 {
+    Vec2::new(
+        2. * screen.x as f32 - viewport.width as f32 * (1. + anchor.x),
+        2. * screen.y as f32 - viewport.height as f32 * (1. + anchor.y),
+    )
+}
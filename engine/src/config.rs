@@ -0,0 +1,51 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// `name` next to the executable rather than in whatever directory the game was started from.
+pub fn path(name: impl AsRef<Path>) -> PathBuf {
+    let dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.to_path_buf()))
+        .unwrap_or_default();
+    dir.join(name)
+}
+
+/// Reads a player-editable config file. A missing file is created from `default` so players
+/// have something to edit, and `None` is returned for a missing or unreadable file, leaving the
+/// caller with its defaults.
+pub fn load_or_create(path: impl AsRef<Path>, default: impl FnOnce() -> String) -> Option<String> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            if let Err(err) = fs::write(path, default()) {
+                log::warn!("Failed to write {}: {}", path.display(), err);
+            }
+            None
+        }
+        Err(err) => {
+            log::warn!("Failed to read {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_files_are_created() {
+        let path = std::env::temp_dir().join(format!("engine-config-{}.cfg", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(load_or_create(&path, || "Fire = Space\n".to_string()), None);
+        assert_eq!(
+            load_or_create(&path, || unreachable!()).as_deref(),
+            Some("Fire = Space\n")
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
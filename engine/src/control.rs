@@ -0,0 +1,95 @@
+use std::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Arc,
+};
+
+use winit::dpi::PhysicalSize;
+
+use crate::replay::InputLog;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    BorderlessFullscreen,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineCommand {
+    Exit,
+    SetWindowMode(WindowMode),
+    SetResolution(PhysicalSize<u32>),
+    SetTitle(String),
+    /// Saves the next frame to a timestamped png in `screenshots/`, same as pressing F12.
+    Screenshot,
+    /// Turns the IME on or off, only needed for text that isn't typed key by key.
+    SetTextInput(bool),
+}
+
+pub(crate) fn control_channel(
+    headless: bool,
+    replay: Option<InputLog>,
+) -> (EngineControl, Receiver<EngineCommand>) {
+    let (tx, rx) = channel();
+    let replay = replay.map(Arc::new);
+    (
+        EngineControl {
+            tx,
+            headless,
+            replay,
+        },
+        rx,
+    )
+}
+
+/// Lets a game talk back to the engine, commands are applied before the next update.
+#[derive(Clone, Debug)]
+pub struct EngineControl {
+    tx: Sender<EngineCommand>,
+    headless: bool,
+    replay: Option<Arc<InputLog>>,
+}
+
+impl EngineControl {
+    /// Whether the game runs in a [`crate::headless::HeadlessRunner`], e.g. to skip opening an
+    /// audio device or writing settings files during tests.
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// The session [`crate::headless::HeadlessRunner::with_replay`] plays back, so `init` can
+    /// set up the same way it did when it was recorded, e.g. with its seed.
+    pub fn replay_log(&self) -> Option<&InputLog> {
+        self.replay.as_deref()
+    }
+
+    pub fn exit(&self) {
+        self.send(EngineCommand::Exit);
+    }
+
+    pub fn set_window_mode(&self, mode: WindowMode) {
+        self.send(EngineCommand::SetWindowMode(mode));
+    }
+
+    pub fn set_resolution(&self, resolution: PhysicalSize<u32>) {
+        self.send(EngineCommand::SetResolution(resolution));
+    }
+
+    pub fn set_title(&self, title: impl Into<String>) {
+        self.send(EngineCommand::SetTitle(title.into()));
+    }
+
+    pub fn screenshot(&self) {
+        self.send(EngineCommand::Screenshot);
+    }
+
+    /// Call with `true` while a text field has focus, e.g. for players typing with an IME.
+    pub fn set_text_input(&self, enabled: bool) {
+        self.send(EngineCommand::SetTextInput(enabled));
+    }
+
+    fn send(&self, command: EngineCommand) {
+        if let Err(err) = self.tx.send(command) {
+            log::warn!("Failed to send engine command: {}", err);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::mpsc::Receiver;
+
+use anyhow::Result;
+
+use crate::{
+    control::{control_channel, EngineCommand},
+    input::InputState,
+    profiler,
+    recording::{Recorder, Recording},
+    replay::{InputLog, LoggedEvent},
+    EngineInitInfo, EverythingToDraw, Game, Input, Lifecycle, SoftwareRenderer,
+};
+
+pub struct ScriptedFrame {
+    pub inputs: Vec<Input>,
+    pub dt: f32,
+}
+
+impl ScriptedFrame {
+    pub fn idle(dt: f32) -> Self {
+        Self { inputs: vec![], dt }
+    }
+}
+
+/// Drives a [`Game`] without a window or a renderer, e.g. for tests on machines without a GPU.
+pub struct HeadlessRunner<G: Game> {
+    init_info: EngineInitInfo,
+    commands: Receiver<EngineCommand>,
+    input_state: InputState,
+    replay: Option<InputLog>,
+    game: G,
+}
+
+impl<G: Game> HeadlessRunner<G> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self::init(None)
+    }
+
+    /// For [`HeadlessRunner::replay`], the game gets the log through
+    /// [`EngineControl::replay_log`](crate::EngineControl::replay_log) to start out the same way
+    /// as the recorded session.
+    pub fn with_replay(log: InputLog) -> Self {
+        Self::init(Some(log))
+    }
+
+    fn init(replay: Option<InputLog>) -> Self {
+        let (control, commands) = control_channel(true, replay.clone());
+        let (init_info, game) = G::init(control);
+        if let Some(path) = &init_info.profiler_csv {
+            if let Err(err) = profiler::dump_csv(path) {
+                log::error!("Failed to create profiler csv: {}", err);
+            }
+        }
+        Self {
+            init_info,
+            commands,
+            input_state: InputState::default(),
+            replay,
+            game,
+        }
+    }
+
+    pub fn init_info(&self) -> &EngineInitInfo {
+        &self.init_info
+    }
+
+    /// Commands the game sent since the last call, there is no window to apply them to.
+    pub fn take_commands(&self) -> Vec<EngineCommand> {
+        self.commands.try_iter().collect()
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut G {
+        &mut self.game
+    }
+
+    pub fn input(&mut self, input: Input) {
+        self.input_state.handle(&input);
+        self.game.input(input);
+    }
+
+    pub fn lifecycle(&mut self, event: Lifecycle) {
+        self.game.lifecycle(event);
+    }
+
+    /// Updates the game by `dt` and returns what it would draw at the end of that update.
+    pub fn step(&mut self, dt: f32) -> EverythingToDraw {
+        profiler::time("update", || self.game.update(dt, &self.input_state));
+        self.input_state.end_tick();
+        let to_draw = profiler::time("draw", || self.game.draw(1.));
+        profiler::end_frame();
+        to_draw
+    }
+
+    pub fn run_frame(&mut self, frame: ScriptedFrame) -> EverythingToDraw {
+        for input in frame.inputs {
+            self.input(input);
+        }
+        self.step(frame.dt)
+    }
+
+    pub fn run(
+        &mut self,
+        frames: impl IntoIterator<Item = ScriptedFrame>,
+    ) -> Vec<EverythingToDraw> {
+        frames.into_iter().map(|f| self.run_frame(f)).collect()
+    }
+
+    /// Plays the session given to [`HeadlessRunner::with_replay`] back at the game's tick rate,
+    /// e.g. to keep a recorded bug fixed. Does nothing the second time or without a session.
+    pub fn replay(&mut self) -> Vec<EverythingToDraw> {
+        let Some(log) = self.replay.take() else {
+            return vec![];
+        };
+        let dt = (self.init_info.tick_rate as f32).recip();
+        let mut events = log.events.iter().peekable();
+        (0..log.len())
+            .map(|tick| {
+                while let Some((_, event)) = events.next_if(|(t, _)| *t <= tick) {
+                    match event.clone() {
+                        LoggedEvent::Input(input) => self.input(input),
+                        LoggedEvent::Lifecycle(event) => self.lifecycle(event),
+                    }
+                }
+                self.step(dt)
+            })
+            .collect()
+    }
+
+    /// Like [`HeadlessRunner::run`], but renders the frames in software and saves them.
+    pub fn record(
+        &mut self,
+        recording: &Recording,
+        frames: impl IntoIterator<Item = ScriptedFrame>,
+    ) -> Result<()> {
+        let resolution = self.init_info.resolution;
+        let mut renderer = SoftwareRenderer::new(resolution.width, resolution.height);
+        let mut recorder = Recorder::new(recording)?;
+        for frame in frames {
+            let to_draw = self.run_frame(frame);
+            if recorder.next_frame() {
+                renderer.rasterize(&to_draw);
+                recorder.save(renderer.image());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<G: Game> Drop for HeadlessRunner<G> {
+    fn drop(&mut self) {
+        self.game.shutdown();
+        profiler::flush();
+    }
+}
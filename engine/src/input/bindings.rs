@@ -0,0 +1,432 @@
+use std::{fmt, fs, io, path::Path};
+
+use winit::{
+    event::{ElementState, MouseButton},
+    keyboard::{Key, ModifiersState, NamedKey, SmolStr},
+};
+
+use crate::config;
+
+use super::{GamepadAxis, GamepadButton, Input, InputState};
+
+/// Something the player can do, usually a fieldless enum listing every action of a game.
+pub trait Action: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+    /// How the action is written in the bindings file.
+    fn name(self) -> &'static str;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+    /// One direction of an axis, so a stick can be bound to both `TurnLeft` and `TurnRight`.
+    /// Only has a [`Bindings::value`], never a [`Bindings::state`].
+    Axis {
+        axis: GamepadAxis,
+        positive: bool,
+    },
+    /// A key with exactly these modifiers held, written like `Ctrl+s` or `Ctrl+Shift+s`. Needs
+    /// to know what else is held, so it only works with the queries taking an [`InputState`].
+    Shortcut {
+        modifiers: ModifiersState,
+        key: Key,
+    },
+}
+
+impl Binding {
+    /// The binding for a character key, e.g. `Binding::char("w")`.
+    pub fn char(c: &str) -> Self {
+        Self::Key(Key::Character(SmolStr::new(c.to_lowercase())))
+    }
+
+    pub fn named(key: NamedKey) -> Self {
+        Self::Key(Key::Named(key))
+    }
+
+    fn matches(&self, input: &Input) -> Option<ElementState> {
+        match (self, input) {
+            (Self::Key(Key::Character(bound)), Input::Keyboard { key: Key::Character(c), state })
+                // shift shouldn't stop a binding from working
+                if c.to_lowercase() == bound.as_str() => Some(*state),
+            (Self::Key(bound), Input::Keyboard { key, state }) if bound == key => Some(*state),
+            (Self::Mouse(bound), Input::MouseButton { btn, state }) if bound == btn => Some(*state),
+            (Self::Gamepad(bound), Input::GamepadButton { btn, state, .. }) if bound == btn => {
+                Some(*state)
+            }
+            _ => None,
+        }
+    }
+
+    fn value(&self, input: &Input) -> Option<f32> {
+        match (self, input) {
+            (Self::Axis { axis, positive }, Input::GamepadAxis { axis: a, value, .. })
+                if axis == a =>
+            {
+                let value = if *positive { *value } else { -value };
+                Some(value.clamp(0., 1.))
+            }
+            _ => self
+                .matches(input)
+                .map(|state| state.is_pressed() as u8 as f32),
+        }
+    }
+}
+
+const MODIFIERS: &[(ModifiersState, &str)] = &[
+    (ModifiersState::CONTROL, "Ctrl"),
+    (ModifiersState::SHIFT, "Shift"),
+    (ModifiersState::ALT, "Alt"),
+    (ModifiersState::SUPER, "Super"),
+];
+
+// the keys that can be written in a bindings file by name, anything else has to be a character
+const NAMED_KEYS: &[NamedKey] = &[
+    NamedKey::Space,
+    NamedKey::Enter,
+    NamedKey::Escape,
+    NamedKey::Tab,
+    NamedKey::Backspace,
+    NamedKey::Delete,
+    NamedKey::ArrowUp,
+    NamedKey::ArrowDown,
+    NamedKey::ArrowLeft,
+    NamedKey::ArrowRight,
+    NamedKey::Shift,
+    NamedKey::Control,
+    NamedKey::Alt,
+    NamedKey::Home,
+    NamedKey::End,
+    NamedKey::PageUp,
+    NamedKey::PageDown,
+    NamedKey::F1,
+    NamedKey::F2,
+    NamedKey::F3,
+    NamedKey::F4,
+    NamedKey::F5,
+    NamedKey::F6,
+    NamedKey::F7,
+    NamedKey::F8,
+    NamedKey::F9,
+    NamedKey::F10,
+    NamedKey::F11,
+    NamedKey::F12,
+];
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // a bare `,` would split the list of bindings
+            Self::Key(Key::Character(c)) if c == "," => write!(f, "Comma"),
+            Self::Key(Key::Character(c)) => write!(f, "{}", c),
+            Self::Key(Key::Named(key)) => write!(f, "{:?}", key),
+            Self::Key(key) => write!(f, "{:?}", key),
+            Self::Mouse(MouseButton::Other(n)) => write!(f, "Mouse{}", n),
+            Self::Mouse(btn) => write!(f, "Mouse{:?}", btn),
+            Self::Gamepad(btn) => write!(f, "Pad{:?}", btn),
+            Self::Axis { axis, positive } => {
+                write!(f, "{:?}{}", axis, if *positive { '+' } else { '-' })
+            }
+            Self::Shortcut { modifiers, key } => {
+                for (modifier, name) in MODIFIERS {
+                    if modifiers.contains(*modifier) {
+                        write!(f, "{}+", name)?;
+                    }
+                }
+                write!(f, "{}", Self::Key(key.clone()))
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = ModifiersState::empty();
+        let mut rest = s;
+        while let Some((modifier, name)) = MODIFIERS
+            .iter()
+            .find(|(_, name)| rest.strip_prefix(name).is_some_and(|r| r.starts_with('+')))
+        {
+            modifiers |= *modifier;
+            rest = &rest[name.len() + 1..];
+        }
+        if !modifiers.is_empty() {
+            return match rest.parse()? {
+                Self::Key(key) => Ok(Self::Shortcut { modifiers, key }),
+                _ => Err(s.to_string()),
+            };
+        }
+
+        if s == "Comma" {
+            return Ok(Self::char(","));
+        }
+        if let Some(key) = NAMED_KEYS.iter().find(|key| format!("{:?}", key) == s) {
+            return Ok(Self::named(*key));
+        }
+        if let Some(btn) = GamepadButton::ALL
+            .iter()
+            .find(|btn| s.strip_prefix("Pad") == Some(&format!("{:?}", btn)))
+        {
+            return Ok(Self::Gamepad(*btn));
+        }
+        if let Some((axis, sign)) = s.split_at_checked(s.len().saturating_sub(1)) {
+            if let (Some(axis), "+" | "-") = (
+                GamepadAxis::ALL.iter().find(|a| format!("{:?}", a) == axis),
+                sign,
+            ) {
+                return Ok(Self::Axis {
+                    axis: *axis,
+                    positive: sign == "+",
+                });
+            }
+        }
+        if let Some(btn) = s.strip_prefix("Mouse") {
+            return Ok(Self::Mouse(match btn {
+                "Left" => MouseButton::Left,
+                "Right" => MouseButton::Right,
+                "Middle" => MouseButton::Middle,
+                "Back" => MouseButton::Back,
+                "Forward" => MouseButton::Forward,
+                other => MouseButton::Other(other.parse().map_err(|_| s.to_string())?),
+            }));
+        }
+        match s.chars().count() {
+            1 => Ok(Self::char(s)),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+/// Maps inputs to actions, each action can have any number of bindings.
+#[derive(Clone, Debug)]
+pub struct Bindings<A: Action> {
+    bindings: Vec<(A, Binding)>,
+}
+
+impl<A: Action> Bindings<A> {
+    pub fn new(bindings: impl IntoIterator<Item = (A, Binding)>) -> Self {
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+
+    /// Reads `path` on top of `defaults`, an action listed in the file loses its default
+    /// bindings. A missing file is created from the defaults so players have something to edit.
+    pub fn load_or_create(path: impl AsRef<Path>, defaults: Self) -> Self {
+        let mut bindings = defaults;
+        if let Some(text) = config::load_or_create(path, || bindings.config()) {
+            bindings.apply_config(&text);
+        }
+        bindings
+    }
+
+    // one `Action = binding, binding` per line
+    fn apply_config(&mut self, text: &str) {
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, bound)) = line.split_once('=') else {
+                log::warn!("Ignoring binding line without `=`: {}", line);
+                continue;
+            };
+            let Some(&action) = A::ALL.iter().find(|a| a.name() == name.trim()) else {
+                log::warn!("Ignoring bindings for unknown action {}", name.trim());
+                continue;
+            };
+            self.clear(action);
+            for binding in bound.split(',').map(str::trim).filter(|b| !b.is_empty()) {
+                match binding.parse() {
+                    Ok(binding) => self.bind(action, binding),
+                    Err(binding) => log::warn!("Ignoring unknown binding {}", binding),
+                }
+            }
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.config())
+    }
+
+    fn config(&self) -> String {
+        A::ALL
+            .iter()
+            .map(|&action| {
+                let bound = self
+                    .bindings_for(action)
+                    .map(ToString::to_string)
+                    // keys without a name in `NAMED_KEYS` would be dropped on the next load anyway
+                    .filter(|binding| match binding.parse::<Binding>() {
+                        Ok(_) => true,
+                        Err(_) => {
+                            log::warn!("Can't save binding {} for {}", binding, action.name());
+                            false
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} = {}\n", action.name(), bound)
+            })
+            .collect()
+    }
+
+    pub fn bind(&mut self, action: A, binding: Binding) {
+        if !self.bindings.contains(&(action, binding.clone())) {
+            self.bindings.push((action, binding));
+        }
+    }
+
+    pub fn unbind(&mut self, action: A, binding: &Binding) {
+        self.bindings.retain(|(a, b)| *a != action || b != binding);
+    }
+
+    pub fn clear(&mut self, action: A) {
+        self.bindings.retain(|(a, _)| *a != action);
+    }
+
+    pub fn bindings_for(&self, action: A) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .filter(move |(a, _)| *a == action)
+            .map(|(_, b)| b)
+    }
+
+    /// How far `input` pushes `action` from 0 to 1, buttons and keys are either. Unlike
+    /// [`Bindings::state`] this includes axes.
+    pub fn value(&self, action: A, input: &Input) -> Option<f32> {
+        self.bindings_for(action).find_map(|b| b.value(input))
+    }
+
+    /// The state `input` puts `action` in, if it is bound to it at all.
+    pub fn state(&self, action: A, input: &Input) -> Option<ElementState> {
+        self.bindings_for(action).find_map(|b| b.matches(input))
+    }
+
+    pub fn pressed(&self, action: A, input: &Input) -> bool {
+        self.state(action, input) == Some(ElementState::Pressed)
+    }
+
+    pub fn released(&self, action: A, input: &Input) -> bool {
+        self.state(action, input) == Some(ElementState::Released)
+    }
+
+    pub fn held(&self, action: A, state: &InputState) -> bool {
+        self.bindings_for(action).any(|b| state.is_held(b))
+    }
+
+    /// Like [`Bindings::held`], but from 0 to 1 with axes included, the strongest binding wins.
+    pub fn held_value(&self, action: A, state: &InputState) -> f32 {
+        self.bindings_for(action)
+            .map(|b| match b {
+                Binding::Axis { axis, positive } => {
+                    let value = state.axis(*axis);
+                    (if *positive { value } else { -value }).clamp(0., 1.)
+                }
+                b => state.is_held(b) as u8 as f32,
+            })
+            .fold(0., f32::max)
+    }
+
+    pub fn just_pressed(&self, action: A, state: &InputState) -> bool {
+        self.bindings_for(action).any(|b| state.just_pressed(b))
+    }
+
+    pub fn just_released(&self, action: A, state: &InputState) -> bool {
+        self.bindings_for(action).any(|b| state.just_released(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum TestAction {
+        Fire,
+        Jump,
+    }
+
+    impl Action for TestAction {
+        const ALL: &'static [Self] = &[Self::Fire, Self::Jump];
+
+        fn name(self) -> &'static str {
+            match self {
+                Self::Fire => "Fire",
+                Self::Jump => "Jump",
+            }
+        }
+    }
+
+    fn every_binding() -> Vec<Binding> {
+        let mut bindings = vec![Binding::char("w"), Binding::char(","), Binding::char("+")];
+        bindings.extend(NAMED_KEYS.iter().map(|&key| Binding::named(key)));
+        bindings.extend(
+            [
+                MouseButton::Left,
+                MouseButton::Right,
+                MouseButton::Middle,
+                MouseButton::Back,
+                MouseButton::Forward,
+                MouseButton::Other(7),
+            ]
+            .map(Binding::Mouse),
+        );
+        bindings.extend(GamepadButton::ALL.iter().map(|&btn| Binding::Gamepad(btn)));
+        for &axis in GamepadAxis::ALL {
+            for positive in [true, false] {
+                bindings.push(Binding::Axis { axis, positive });
+            }
+        }
+        for key in [
+            Key::Character("s".into()),
+            Key::Character(",".into()),
+            Key::Named(NamedKey::F5),
+        ] {
+            bindings.push(Binding::Shortcut {
+                modifiers: ModifiersState::CONTROL,
+                key: key.clone(),
+            });
+            bindings.push(Binding::Shortcut {
+                modifiers: ModifiersState::all(),
+                key,
+            });
+        }
+        bindings
+    }
+
+    #[test]
+    fn binding_round_trip() {
+        for binding in every_binding() {
+            assert_eq!(
+                binding.to_string().parse(),
+                Ok(binding.clone()),
+                "{}",
+                binding
+            );
+        }
+    }
+
+    #[test]
+    fn config_round_trip() {
+        let saved = Bindings::new(every_binding().into_iter().map(|b| (TestAction::Fire, b)));
+        let mut loaded = Bindings::new([]);
+        loaded.apply_config(&saved.config());
+        assert_eq!(
+            loaded.bindings_for(TestAction::Fire).collect::<Vec<_>>(),
+            saved.bindings_for(TestAction::Fire).collect::<Vec<_>>(),
+        );
+        assert_eq!(loaded.bindings_for(TestAction::Jump).count(), 0);
+    }
+
+    #[test]
+    fn unnamed_keys_are_not_saved() {
+        let bindings = Bindings::new([
+            (TestAction::Fire, Binding::named(NamedKey::MediaPlay)),
+            (TestAction::Fire, Binding::char("f")),
+        ]);
+        assert_eq!(bindings.config(), "Fire = f\nJump = \n");
+    }
+}
@@ -0,0 +1,177 @@
+use gilrs::{EventType, Gilrs};
+use winit::event::ElementState;
+
+use super::Input;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub const ALL: &'static [Self] = &[
+        Self::South,
+        Self::East,
+        Self::North,
+        Self::West,
+        Self::LeftBumper,
+        Self::RightBumper,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+        Self::Select,
+        Self::Start,
+        Self::Mode,
+        Self::LeftThumb,
+        Self::RightThumb,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+    ];
+
+    fn from_gilrs(btn: gilrs::Button) -> Option<Self> {
+        use gilrs::Button as B;
+        Some(match btn {
+            B::South => Self::South,
+            B::East => Self::East,
+            B::North => Self::North,
+            B::West => Self::West,
+            B::LeftTrigger => Self::LeftBumper,
+            B::RightTrigger => Self::RightBumper,
+            B::LeftTrigger2 => Self::LeftTrigger,
+            B::RightTrigger2 => Self::RightTrigger,
+            B::Select => Self::Select,
+            B::Start => Self::Start,
+            B::Mode => Self::Mode,
+            B::LeftThumb => Self::LeftThumb,
+            B::RightThumb => Self::RightThumb,
+            B::DPadUp => Self::DPadUp,
+            B::DPadDown => Self::DPadDown,
+            B::DPadLeft => Self::DPadLeft,
+            B::DPadRight => Self::DPadRight,
+            B::C | B::Z | B::Unknown => return None,
+        })
+    }
+}
+
+/// Sticks go from -1 to 1 with positive x to the right and positive y up, triggers from 0 to 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub const ALL: &'static [Self] = &[
+        Self::LeftStickX,
+        Self::LeftStickY,
+        Self::RightStickX,
+        Self::RightStickY,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+    ];
+}
+
+/// Builds the inputs a real gamepad would send, for feeding a game from tests or scripts.
+#[derive(Clone, Copy, Debug)]
+pub struct VirtualGamepad(pub usize);
+
+impl VirtualGamepad {
+    pub fn press(self, btn: GamepadButton) -> Input {
+        Input::GamepadButton {
+            gamepad: self.0,
+            btn,
+            state: ElementState::Pressed,
+        }
+    }
+
+    pub fn release(self, btn: GamepadButton) -> Input {
+        Input::GamepadButton {
+            gamepad: self.0,
+            btn,
+            state: ElementState::Released,
+        }
+    }
+
+    pub fn axis(self, axis: GamepadAxis, value: f32) -> Input {
+        Input::GamepadAxis {
+            gamepad: self.0,
+            axis,
+            value,
+        }
+    }
+}
+
+/// The physical gamepads, polled by the engine every frame.
+pub(crate) struct Gamepads {
+    gilrs: Gilrs,
+}
+
+impl Gamepads {
+    pub(crate) fn new() -> Option<Self> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Self { gilrs }),
+            Err(err) => {
+                log::warn!("Gamepads are unavailable: {}", err);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn poll(&mut self) -> Vec<Input> {
+        let mut inputs = vec![];
+        while let Some(event) = self.gilrs.next_event() {
+            let gamepad = event.id.into();
+            let pad = VirtualGamepad(gamepad);
+            match event.event {
+                EventType::ButtonPressed(btn, _) => {
+                    inputs.extend(GamepadButton::from_gilrs(btn).map(|btn| pad.press(btn)))
+                }
+                EventType::ButtonReleased(btn, _) => {
+                    inputs.extend(GamepadButton::from_gilrs(btn).map(|btn| pad.release(btn)))
+                }
+                // analog triggers report as buttons with a value
+                EventType::ButtonChanged(gilrs::Button::LeftTrigger2, value, _) => {
+                    inputs.push(pad.axis(GamepadAxis::LeftTrigger, value))
+                }
+                EventType::ButtonChanged(gilrs::Button::RightTrigger2, value, _) => {
+                    inputs.push(pad.axis(GamepadAxis::RightTrigger, value))
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    let axis = match axis {
+                        gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+                        gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+                        gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+                        gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+                        _ => continue,
+                    };
+                    inputs.push(pad.axis(axis, value));
+                }
+                EventType::Connected => log::info!("Gamepad {} connected", gamepad),
+                EventType::Disconnected => log::info!("Gamepad {} disconnected", gamepad),
+                _ => (),
+            }
+        }
+        inputs
+    }
+}
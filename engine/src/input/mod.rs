@@ -0,0 +1,118 @@
+mod bindings;
+mod gamepad;
+mod state;
+
+pub use bindings::{Action, Binding, Bindings};
+pub use gamepad::{GamepadAxis, GamepadButton, VirtualGamepad};
+pub use state::InputState;
+
+pub(crate) use gamepad::Gamepads;
+
+use ultraviolet::Vec2;
+use winit::{
+    dpi::PhysicalPosition,
+    event::{
+        ElementState, Ime, KeyEvent, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent,
+    },
+    keyboard::{Key, ModifiersState},
+};
+
+// trackpads scroll in pixels, wheels in lines
+const PIXELS_PER_LINE: f64 = 40.;
+
+#[derive(Clone, Debug)]
+pub enum Input {
+    Keyboard {
+        key: Key,
+        state: ElementState,
+    },
+    Cursor(PhysicalPosition<f64>),
+    MouseButton {
+        btn: MouseButton,
+        state: ElementState,
+    },
+    /// In lines, positive `y` is scrolling up and positive `x` is scrolling left.
+    MouseWheel(Vec2),
+    /// `id` tells fingers apart while they're on the screen.
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        pos: PhysicalPosition<f64>,
+    },
+    /// Which of ctrl, shift, alt and super are now held.
+    Modifiers(ModifiersState),
+    /// `gamepad` tells connected gamepads apart.
+    GamepadButton {
+        gamepad: usize,
+        btn: GamepadButton,
+        state: ElementState,
+    },
+    GamepadAxis {
+        gamepad: usize,
+        axis: GamepadAxis,
+        value: f32,
+    },
+    /// Typed text, from a key press or committed by an IME. Comes after the `Keyboard` input of
+    /// the same key, control characters like backspace and enter only show up as `Keyboard`.
+    Text(String),
+}
+
+impl TryFrom<WindowEvent> for Input {
+    type Error = ();
+
+    fn try_from(value: WindowEvent) -> Result<Self, Self::Error> {
+        Ok(match value {
+            WindowEvent::CursorMoved { position, .. } => Self::Cursor(position),
+            WindowEvent::MouseInput { state, button, .. } => {
+                Self::MouseButton { btn: button, state }
+            }
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state, logical_key, ..
+                },
+                ..
+            } => Self::Keyboard {
+                key: logical_key,
+                state,
+            },
+            WindowEvent::Ime(Ime::Commit(text)) => Self::Text(text),
+            WindowEvent::MouseWheel { delta, .. } => Self::MouseWheel(match delta {
+                MouseScrollDelta::LineDelta(x, y) => Vec2::new(x, y),
+                MouseScrollDelta::PixelDelta(pos) => Vec2::new(
+                    (pos.x / PIXELS_PER_LINE) as f32,
+                    (pos.y / PIXELS_PER_LINE) as f32,
+                ),
+            }),
+            WindowEvent::Touch(Touch {
+                id,
+                phase,
+                location,
+                ..
+            }) => Self::Touch {
+                id,
+                phase,
+                pos: location,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => Self::Modifiers(modifiers.state()),
+            _ => Err(())?,
+        })
+    }
+}
+
+/// The text a key press typed, which winit puts in the same event as the key itself.
+pub(crate) fn typed_text(event: &WindowEvent) -> Option<Input> {
+    let WindowEvent::KeyboardInput {
+        event:
+            KeyEvent {
+                state: ElementState::Pressed,
+                text: Some(text),
+                ..
+            },
+        ..
+    } = event
+    else {
+        return None;
+    };
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    (!text.is_empty()).then_some(Input::Text(text))
+}
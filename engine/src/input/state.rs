@@ -0,0 +1,276 @@
+use ultraviolet::Vec2;
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, TouchPhase},
+    keyboard::{Key, ModifiersState},
+};
+
+use super::{Binding, GamepadAxis, Input};
+
+/// Everything held down and where the cursor is, kept up to date by the engine so `update` can
+/// poll it instead of tracking events. Gamepads are merged, a button counts as held if it is on
+/// any of them.
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    held: Vec<Binding>,
+    // since the last update
+    pressed: Vec<Binding>,
+    released: Vec<Binding>,
+    cursor: Option<PhysicalPosition<f64>>,
+    axes: Vec<(GamepadAxis, f32)>,
+    modifiers: ModifiersState,
+    // since the last update
+    wheel: Vec2,
+    touches: Vec<(u64, PhysicalPosition<f64>)>,
+}
+
+impl InputState {
+    pub fn is_held(&self, button: &Binding) -> bool {
+        match button {
+            Binding::Shortcut { modifiers, key } => {
+                *modifiers == self.modifiers && self.is_held(&Binding::Key(key.clone()))
+            }
+            button => self.held.contains(button),
+        }
+    }
+
+    /// Went down since the previous update, held buttons repeating don't count.
+    pub fn just_pressed(&self, button: &Binding) -> bool {
+        match button {
+            Binding::Shortcut { modifiers, key } => {
+                *modifiers == self.modifiers && self.just_pressed(&Binding::Key(key.clone()))
+            }
+            button => self.pressed.contains(button),
+        }
+    }
+
+    pub fn just_released(&self, button: &Binding) -> bool {
+        match button {
+            Binding::Shortcut { modifiers, key } => {
+                *modifiers == self.modifiers && self.just_released(&Binding::Key(key.clone()))
+            }
+            button => self.released.contains(button),
+        }
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// How far the wheel scrolled since the previous update, see [`Input::MouseWheel`].
+    pub fn wheel(&self) -> Vec2 {
+        self.wheel
+    }
+
+    /// The fingers currently on the screen.
+    pub fn touches(&self) -> &[(u64, PhysicalPosition<f64>)] {
+        &self.touches
+    }
+
+    pub fn cursor(&self) -> Option<PhysicalPosition<f64>> {
+        self.cursor
+    }
+
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes
+            .iter()
+            .find(|(a, _)| *a == axis)
+            .map_or(0., |(_, value)| *value)
+    }
+
+    pub(crate) fn handle(&mut self, input: &Input) {
+        let (button, state) = match input {
+            Input::Keyboard { key, state } => (Binding::Key(normalise(key)), *state),
+            Input::MouseButton { btn, state } => (Binding::Mouse(*btn), *state),
+            Input::GamepadButton { btn, state, .. } => (Binding::Gamepad(*btn), *state),
+            Input::Cursor(position) => {
+                self.cursor = Some(*position);
+                return;
+            }
+            Input::GamepadAxis { axis, value, .. } => {
+                match self.axes.iter_mut().find(|(a, _)| a == axis) {
+                    Some((_, v)) => *v = *value,
+                    None => self.axes.push((*axis, *value)),
+                }
+                return;
+            }
+            Input::MouseWheel(delta) => {
+                self.wheel += *delta;
+                return;
+            }
+            Input::Touch { id, phase, pos } => {
+                self.touches.retain(|(t, _)| t != id);
+                if let TouchPhase::Started | TouchPhase::Moved = phase {
+                    self.touches.push((*id, *pos));
+                }
+                return;
+            }
+            Input::Modifiers(modifiers) => {
+                self.modifiers = *modifiers;
+                return;
+            }
+            Input::Text(_) => return,
+        };
+
+        match state {
+            ElementState::Pressed if !self.held.contains(&button) => {
+                self.held.push(button.clone());
+                self.pressed.push(button);
+            }
+            ElementState::Pressed => (),
+            ElementState::Released => {
+                self.held.retain(|b| *b != button);
+                self.released.push(button);
+            }
+        }
+    }
+
+    /// Forgets the edges once an update has seen them.
+    pub(crate) fn end_tick(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.wheel = Vec2::zero();
+    }
+}
+
+// so letting go of `W` after shift releases a held `w`
+fn normalise(key: &Key) -> Key {
+    match key {
+        Key::Character(c) => Key::Character(c.to_lowercase().into()),
+        key => key.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{GamepadButton, VirtualGamepad};
+    use winit::keyboard::NamedKey;
+
+    fn key(c: &str, state: ElementState) -> Input {
+        Input::Keyboard {
+            key: Key::Character(c.into()),
+            state,
+        }
+    }
+
+    #[test]
+    fn gamepad_button_edges() {
+        let mut state = InputState::default();
+        let pad = VirtualGamepad(0);
+        let south = Binding::Gamepad(GamepadButton::South);
+
+        state.handle(&pad.press(GamepadButton::South));
+        assert!(state.is_held(&south));
+        assert!(state.just_pressed(&south));
+
+        state.end_tick();
+        assert!(state.is_held(&south));
+        assert!(!state.just_pressed(&south));
+        // repeats don't press it again
+        state.handle(&pad.press(GamepadButton::South));
+        assert!(!state.just_pressed(&south));
+
+        state.handle(&pad.release(GamepadButton::South));
+        assert!(!state.is_held(&south));
+        assert!(state.just_released(&south));
+
+        state.end_tick();
+        assert!(!state.just_released(&south));
+    }
+
+    #[test]
+    fn gamepad_axes() {
+        let mut state = InputState::default();
+        let pad = VirtualGamepad(1);
+
+        state.handle(&pad.axis(GamepadAxis::LeftStickX, 0.75));
+        assert_eq!(state.axis(GamepadAxis::LeftStickX), 0.75);
+        assert_eq!(state.axis(GamepadAxis::LeftStickY), 0.);
+
+        // sticks only report changes, so a held one keeps its value between updates
+        state.end_tick();
+        assert_eq!(state.axis(GamepadAxis::LeftStickX), 0.75);
+
+        state.handle(&pad.axis(GamepadAxis::LeftStickX, 0.));
+        state.end_tick();
+        assert_eq!(state.axis(GamepadAxis::LeftStickX), 0.);
+    }
+
+    #[test]
+    fn wheel_adds_up_until_the_update() {
+        let mut state = InputState::default();
+        state.handle(&Input::MouseWheel(Vec2::new(0., 1.)));
+        state.handle(&Input::MouseWheel(Vec2::new(0.5, 2.)));
+        assert_eq!(state.wheel(), Vec2::new(0.5, 3.));
+
+        state.end_tick();
+        assert_eq!(state.wheel(), Vec2::zero());
+    }
+
+    #[test]
+    fn touches_follow_their_fingers() {
+        let mut state = InputState::default();
+        let touch = |id, phase, x| Input::Touch {
+            id,
+            phase,
+            pos: PhysicalPosition::new(x, 0.),
+        };
+
+        state.handle(&touch(1, TouchPhase::Started, 10.));
+        state.handle(&touch(2, TouchPhase::Started, 20.));
+        state.handle(&touch(1, TouchPhase::Moved, 15.));
+        state.end_tick();
+        let mut touches = state.touches().to_vec();
+        touches.sort_by_key(|(id, _)| *id);
+        assert_eq!(
+            touches,
+            [
+                (1, PhysicalPosition::new(15., 0.)),
+                (2, PhysicalPosition::new(20., 0.)),
+            ]
+        );
+
+        state.handle(&touch(1, TouchPhase::Ended, 15.));
+        assert_eq!(state.touches(), [(2, PhysicalPosition::new(20., 0.))]);
+        state.handle(&touch(2, TouchPhase::Cancelled, 20.));
+        assert!(state.touches().is_empty());
+    }
+
+    #[test]
+    fn shortcuts_need_exactly_their_modifiers() {
+        let mut state = InputState::default();
+        let save = Binding::Shortcut {
+            modifiers: ModifiersState::CONTROL,
+            key: Key::Character("s".into()),
+        };
+
+        state.handle(&key("s", ElementState::Pressed));
+        assert!(state.is_held(&Binding::char("s")));
+        assert!(!state.just_pressed(&save));
+        state.handle(&key("s", ElementState::Released));
+        state.end_tick();
+
+        state.handle(&Input::Modifiers(ModifiersState::CONTROL));
+        state.handle(&key("s", ElementState::Pressed));
+        assert!(state.just_pressed(&save));
+        assert!(state.is_held(&save));
+        state.end_tick();
+
+        // shift is held as well now, and it turns the key into `S`
+        state.handle(&Input::Modifiers(
+            ModifiersState::CONTROL | ModifiersState::SHIFT,
+        ));
+        assert!(!state.is_held(&save));
+        state.handle(&key("S", ElementState::Released));
+        assert!(!state.just_released(&save));
+        assert!(!state.is_held(&Binding::char("s")));
+
+        state.handle(&Input::Modifiers(ModifiersState::CONTROL));
+        state.handle(&Input::Keyboard {
+            key: Key::Named(NamedKey::F5),
+            state: ElementState::Pressed,
+        });
+        assert!(!state.just_pressed(&save));
+    }
+}
@@ -1,44 +1,104 @@
-mod input;
+mod camera;
+mod control;
+mod lifecycle;
 mod render;
+mod timestep;
 
 pub mod audio;
+pub mod config;
+pub mod headless;
+pub mod image;
+pub mod input;
+pub mod logging;
 pub mod physics;
+pub mod profiler;
+pub mod recording;
+pub mod replay;
+pub mod scene;
 pub mod text;
 
-use std::time::Instant;
+use std::{path::PathBuf, sync::mpsc::Receiver, time::Instant};
 
 use anyhow::Result;
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalSize,
-    event::WindowEvent,
+    event::{ElementState, WindowEvent},
     event_loop::{ActiveEventLoop, EventLoop},
+    keyboard::{Key, NamedKey},
     window::{Fullscreen, Window, WindowId},
 };
 
-use self::render::Renderer;
+use self::{
+    control::control_channel,
+    headless::{HeadlessRunner, ScriptedFrame},
+    input::{Gamepads, InputState},
+    recording::{Recorder, Recording},
+    replay::{InputPlayer, InputRecorder, LoggedEvent, Replay},
+    timestep::FixedTimestep,
+};
 
+pub use camera::{screen_to_ui, Camera};
+pub use control::{EngineCommand, EngineControl, WindowMode};
 pub use input::Input;
-pub use render::{EverythingToDraw, RenderLiteral, ShapeLiteral};
+pub use lifecycle::Lifecycle;
+pub use render::{
+    EverythingToDraw, RenderBackend, RenderLiteral, ShapeLiteral, SoftwareRenderer, VulkanRenderer,
+};
 
 pub trait Game {
-    fn init() -> (EngineInitInfo, Self);
-    fn update(&mut self, dt: f32);
+    fn init(control: EngineControl) -> (EngineInitInfo, Self);
+    /// `input` holds what is currently pressed, its just pressed and released edges cover
+    /// everything since the previous `update`.
+    fn update(&mut self, dt: f32, input: &InputState);
     fn input(&mut self, input: Input);
-    fn draw(&self) -> EverythingToDraw;
+    /// `alpha` is how far into the next tick the frame is, for interpolating between states.
+    fn draw(&self, alpha: f32) -> EverythingToDraw;
+    /// Focus, size and minimise changes of the window, e.g. to pause when the player tabs out.
+    fn lifecycle(&mut self, _event: Lifecycle) {}
+    /// Called once before the engine tears everything down.
+    fn shutdown(&mut self) {}
 }
 
 pub struct EngineInitInfo {
     pub windowed: bool,
     pub resizeable: bool,
     pub resolution: PhysicalSize<u32>,
+    /// `update` calls per second, each getting a `dt` of `1 / tick_rate`.
+    pub tick_rate: u32,
+    /// Upper bound on `update` calls per frame when catching up after a stall.
+    pub max_catch_up_steps: u32,
+    /// Starts with the frame timing overlay shown, F3 toggles it either way.
+    pub show_profiler: bool,
+    /// Where to write per-frame timings, see [`profiler::dump_csv`].
+    pub profiler_csv: Option<PathBuf>,
+    /// Usually `Box::<VulkanRenderer>::default()`.
+    pub backend: Box<dyn RenderBackend>,
+    /// Records every frame to disk instead of running in real time.
+    pub recording: Option<Recording>,
+    /// Logs the player's inputs to a file, or plays a logged session back.
+    pub replay: Option<Replay>,
 }
 
 // TODO: better name
 pub struct MainEngineThing<G: Game> {
     last_wait: Option<Instant>,
+    timestep: Option<FixedTimestep>,
+    commands: Option<Receiver<EngineCommand>>,
     window: Option<Window>,
-    renderer: Renderer,
+    renderer: Option<Box<dyn RenderBackend>>,
+    show_profiler: bool,
+    minimised: bool,
+    // hidden behind other windows, only drawing stops, the game isn't told
+    occluded: bool,
+    screenshot: bool,
+    recorder: Option<Recorder>,
+    input_recorder: Option<InputRecorder>,
+    input_player: Option<InputPlayer>,
+    // updates so far, what logged inputs are timed by
+    tick: u64,
+    gamepads: Option<Gamepads>,
+    input_state: InputState,
     game: Option<G>,
 }
 
@@ -46,8 +106,20 @@ impl<G: Game> Default for MainEngineThing<G> {
     fn default() -> Self {
         Self {
             last_wait: None,
+            timestep: None,
+            commands: None,
             window: None,
-            renderer: Renderer::default(),
+            renderer: None,
+            show_profiler: false,
+            minimised: false,
+            occluded: false,
+            screenshot: false,
+            recorder: None,
+            input_recorder: None,
+            input_player: None,
+            tick: 0,
+            gamepads: None,
+            input_state: InputState::default(),
             game: None,
         }
     }
@@ -56,8 +128,39 @@ impl<G: Game> Default for MainEngineThing<G> {
 impl<G: Game> ApplicationHandler for MainEngineThing<G> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         self.last_wait = Some(Instant::now());
-        let (init_info, game) = G::init();
+        let (control, commands) = control_channel(false, None);
+        let (mut init_info, game) = G::init(control);
         self.game = Some(game);
+        self.commands = Some(commands);
+        self.show_profiler = init_info.show_profiler;
+        if let Some(path) = &init_info.profiler_csv {
+            if let Err(err) = profiler::dump_csv(path) {
+                log::error!("Failed to create profiler csv: {}", err);
+            }
+        }
+        if let Some(recording) = &init_info.recording {
+            match Recorder::new(recording) {
+                Ok(recorder) => self.recorder = Some(recorder),
+                Err(err) => log::error!("Failed to start recording: {}", err),
+            }
+        }
+        match init_info.replay.take() {
+            Some(Replay::Record {
+                path,
+                seed,
+                seed_override,
+            }) => match InputRecorder::new(&path, seed, seed_override) {
+                Ok(recorder) => self.input_recorder = Some(recorder),
+                Err(err) => log::error!("Failed to start input log: {:#}", err),
+            },
+            Some(Replay::Play(log)) => self.input_player = Some(InputPlayer::new(log)),
+            None => {}
+        }
+        self.gamepads = Gamepads::new();
+        self.timestep = Some(FixedTimestep::new(
+            init_info.tick_rate,
+            init_info.max_catch_up_steps,
+        ));
         let window = event_loop
             .create_window(
                 Window::default_attributes()
@@ -70,7 +173,8 @@ impl<G: Game> ApplicationHandler for MainEngineThing<G> {
                     }),
             )
             .unwrap();
-        self.renderer.init(&window).unwrap();
+        init_info.backend.init(&window).unwrap();
+        self.renderer = Some(init_info.backend);
         self.window = Some(window);
     }
 
@@ -82,36 +186,243 @@ impl<G: Game> ApplicationHandler for MainEngineThing<G> {
     ) {
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::RedrawRequested if self.minimised || self.occluded => {}
             WindowEvent::RedrawRequested => {
-                self.renderer
-                    .render(
-                        &self.game.as_ref().unwrap().draw(),
-                        self.window.as_ref().unwrap(),
-                    )
+                let timestep = self.timestep.as_ref().unwrap();
+                let (alpha, record) = if self.recorder.is_some() {
+                    // exactly one tick per frame, however long the frame took
+                    self.update(timestep.step());
+                    (1., self.recorder.as_mut().unwrap().next_frame())
+                } else {
+                    (timestep.alpha(), false)
+                };
+                let mut to_draw =
+                    profiler::time("draw", || self.game.as_ref().unwrap().draw(alpha));
+                if self.show_profiler {
+                    to_draw.shapes.append(&mut profiler::overlay());
+                }
+
+                let renderer = self.renderer.as_mut().unwrap();
+                let screenshot = std::mem::take(&mut self.screenshot);
+                if screenshot || record {
+                    renderer.request_screenshot();
+                }
+                renderer
+                    .render(&to_draw, self.window.as_ref().unwrap())
                     .unwrap();
+                profiler::end_frame();
+
+                match renderer.take_screenshot() {
+                    Some(Ok(capture)) => {
+                        if screenshot {
+                            save_screenshot(capture.clone());
+                        }
+                        if record {
+                            self.recorder.as_mut().unwrap().save(capture);
+                        }
+                    }
+                    Some(Err(err)) => log::error!("Failed to capture frame: {:#}", err),
+                    None => {}
+                }
                 self.window.as_ref().unwrap().request_redraw();
             }
-            WindowEvent::Resized(_) => self.renderer.resized = true,
+            WindowEvent::Resized(size) => {
+                self.renderer.as_mut().unwrap().resize(size);
+                if size.width == 0 || size.height == 0 {
+                    self.set_minimised(true);
+                } else {
+                    self.set_minimised(false);
+                    self.lifecycle(Lifecycle::Resized(size));
+                }
+            }
+            WindowEvent::Occluded(occluded) => {
+                self.occluded = occluded;
+                if !occluded {
+                    self.window.as_ref().unwrap().request_redraw();
+                }
+            }
+            WindowEvent::Focused(focused) => self.lifecycle(if focused {
+                Lifecycle::FocusGained
+            } else {
+                Lifecycle::FocusLost
+            }),
+            // the new size arrives in the `Resized` that follows
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.lifecycle(Lifecycle::ScaleFactorChanged(scale_factor));
+            }
             other => {
-                if let Ok(input) = other.try_into() {
-                    self.game.as_mut().unwrap().input(input);
+                let text = input::typed_text(&other);
+                if let Ok(input) = Input::try_from(other) {
+                    if let Input::Keyboard {
+                        key: Key::Named(NamedKey::F3),
+                        state: ElementState::Released,
+                    } = input
+                    {
+                        self.show_profiler = !self.show_profiler;
+                    }
+                    if let Input::Keyboard {
+                        key: Key::Named(NamedKey::F12),
+                        state: ElementState::Released,
+                    } = input
+                    {
+                        self.screenshot = true;
+                    }
+                    self.input(input);
+                }
+                if let Some(text) = text {
+                    self.input(text);
+                }
+            }
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.apply_commands(event_loop);
+        if event_loop.exiting() {
+            return;
+        }
+
+        let gamepad_inputs = self.gamepads.as_mut().map(Gamepads::poll);
+        for input in gamepad_inputs.into_iter().flatten() {
+            self.input(input);
+        }
+
+        // recordings update from `RedrawRequested` instead
+        if self.recorder.is_some() {
+            return;
+        }
+
+        let timestep = self.timestep.as_mut().unwrap();
+        let steps = timestep.advance(self.last_wait.replace(Instant::now()).unwrap().elapsed());
+        let step = timestep.step();
+        for _ in 0..steps {
+            self.update(step);
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(game) = self.game.as_mut() {
+            game.shutdown();
+        }
+        // flushes the frames still being saved
+        self.recorder = None;
+        profiler::flush();
+    }
+}
+
+impl<G: Game> MainEngineThing<G> {
+    fn input(&mut self, input: Input) {
+        // the player watches while a replay runs
+        if self.input_player.is_some() {
+            return;
+        }
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.record(self.tick, &LoggedEvent::Input(input.clone()));
+        }
+        self.apply_input(input);
+    }
+
+    // logged like input, the scenes react to focus and size changes as well
+    fn lifecycle(&mut self, event: Lifecycle) {
+        if self.input_player.is_some() {
+            return;
+        }
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.record(self.tick, &LoggedEvent::Lifecycle(event));
+        }
+        self.game.as_mut().unwrap().lifecycle(event);
+    }
+
+    fn apply_input(&mut self, input: Input) {
+        self.input_state.handle(&input);
+        self.game.as_mut().unwrap().input(input);
+    }
+
+    fn update(&mut self, dt: f32) {
+        if let Some(player) = self.input_player.as_mut() {
+            for event in player.due(self.tick) {
+                match event {
+                    LoggedEvent::Input(input) => self.apply_input(input),
+                    LoggedEvent::Lifecycle(event) => self.game.as_mut().unwrap().lifecycle(event),
                 }
             }
+            if self.input_player.as_ref().unwrap().finished() {
+                log::info!("Replay finished after {} ticks", self.tick);
+                self.input_player = None;
+            }
         }
+        profiler::time("update", || {
+            self.game.as_mut().unwrap().update(dt, &self.input_state)
+        });
+        self.input_state.end_tick();
+        self.tick += 1;
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        self.game.as_mut().unwrap().update(
-            self.last_wait
-                .replace(Instant::now())
-                .unwrap()
-                .elapsed()
-                .as_secs_f32(),
-        );
+    // minimising shows up as a zero sized resize on some platforms and as occlusion on others
+    fn set_minimised(&mut self, minimised: bool) {
+        if self.minimised == minimised {
+            return;
+        }
+        self.minimised = minimised;
+        self.lifecycle(if minimised {
+            Lifecycle::Minimised
+        } else {
+            Lifecycle::Restored
+        });
+        if !minimised {
+            self.window.as_ref().unwrap().request_redraw();
+        }
+    }
+
+    fn apply_commands(&mut self, event_loop: &ActiveEventLoop) {
+        let window = self.window.as_ref().unwrap();
+        for command in self.commands.as_ref().unwrap().try_iter() {
+            match command {
+                EngineCommand::Exit => event_loop.exit(),
+                EngineCommand::SetWindowMode(WindowMode::Windowed) => window.set_fullscreen(None),
+                EngineCommand::SetWindowMode(WindowMode::BorderlessFullscreen) => {
+                    window.set_fullscreen(Some(Fullscreen::Borderless(None)))
+                }
+                EngineCommand::SetResolution(resolution) => {
+                    if let Some(size) = window.request_inner_size(resolution) {
+                        self.renderer.as_mut().unwrap().resize(size);
+                    }
+                }
+                EngineCommand::SetTitle(title) => window.set_title(&title),
+                EngineCommand::Screenshot => self.screenshot = true,
+                EngineCommand::SetTextInput(enabled) => window.set_ime_allowed(enabled),
+            }
+        }
     }
 }
 
+// encoding takes a few frames worth of time
+fn save_screenshot(capture: image::Image) {
+    std::thread::spawn(move || {
+        let path = image::screenshot_path();
+        match capture.save_png(&path) {
+            Ok(()) => log::info!("Saved screenshot to {}", path.display()),
+            Err(err) => log::error!("Failed to save screenshot: {}", err),
+        }
+    });
+}
+
 pub fn run_game<G: Game>() -> Result<()> {
     EventLoop::new()?.run_app(&mut MainEngineThing::<G>::default())?;
     Ok(())
 }
+
+pub fn run_headless<G: Game>(
+    frames: impl IntoIterator<Item = ScriptedFrame>,
+) -> Vec<EverythingToDraw> {
+    HeadlessRunner::<G>::new().run(frames)
+}
+
+/// Records `frames` through the [`SoftwareRenderer`] at the game's resolution, no window or GPU
+/// needed.
+pub fn record_headless<G: Game>(
+    recording: &Recording,
+    frames: impl IntoIterator<Item = ScriptedFrame>,
+) -> Result<()> {
+    HeadlessRunner::<G>::new().record(recording, frames)
+}
@@ -0,0 +1,13 @@
+use winit::dpi::PhysicalSize;
+
+/// Changes to the window itself rather than input aimed at the game.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lifecycle {
+    FocusGained,
+    FocusLost,
+    /// The new inner size, never zero since a zero size is reported as [`Lifecycle::Minimised`].
+    Resized(PhysicalSize<u32>),
+    ScaleFactorChanged(f64),
+    Minimised,
+    Restored,
+}
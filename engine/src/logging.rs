@@ -0,0 +1,225 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, IsTerminal, Write},
+    panic,
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use colorize::AnsiColor;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+pub struct LogConfig {
+    pub level: LevelFilter,
+    /// Overrides `level` for targets starting with the given module path, the longest match wins.
+    pub module_levels: Vec<(String, LevelFilter)>,
+    pub terminal: bool,
+    pub file: Option<LogFile>,
+}
+
+/// A log file that gets rotated to `<path>.1`, `<path>.2`, ... once it grows past `max_bytes`.
+pub struct LogFile {
+    pub path: PathBuf,
+    pub max_bytes: u64,
+    pub keep: u32,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::Info,
+            module_levels: vec![],
+            terminal: true,
+            file: None,
+        }
+    }
+}
+
+impl LogConfig {
+    /// Applies a filter spec like `warn,engine::render=trace,engine::audio=off`.
+    ///
+    /// The logger isn't installed yet at this point, so bad directives are reported on stderr.
+    pub fn with_filters(mut self, spec: &str) -> Self {
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let parsed = match directive.split_once('=') {
+                Some((module, level)) => level
+                    .parse()
+                    .map(|level| self.module_levels.push((module.to_string(), level))),
+                None => directive.parse().map(|level| self.level = level),
+            };
+            if parsed.is_err() {
+                eprintln!("Ignoring invalid log filter '{}'", directive);
+            }
+        }
+        self
+    }
+}
+
+/// Installs the engine logger for the `log` macros, and a panic hook that logs the panic
+/// before the default hook runs, so crashes end up in the log file.
+pub fn init(config: LogConfig) -> Result<()> {
+    let file = match &config.file {
+        Some(file) => Some(RotatingFile::open(file)?),
+        None => None,
+    };
+    let max_level = config
+        .module_levels
+        .iter()
+        .map(|(_, level)| *level)
+        .fold(config.level, Ord::max);
+
+    log::set_boxed_logger(Box::new(Logger {
+        level: config.level,
+        module_levels: config.module_levels,
+        terminal: config.terminal,
+        colour: io::stderr().is_terminal(),
+        file: Mutex::new(file),
+    }))?;
+    log::set_max_level(max_level);
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        log::error!(target: "panic", "{}", info);
+        log::logger().flush();
+        default_hook(info);
+    }));
+
+    Ok(())
+}
+
+struct Logger {
+    level: LevelFilter,
+    module_levels: Vec<(String, LevelFilter)>,
+    terminal: bool,
+    /// No escape codes when stderr is redirected to a file or a pipe.
+    colour: bool,
+    file: Mutex<Option<RotatingFile>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = self
+            .module_levels
+            .iter()
+            .filter(|(module, _)| metadata.target().starts_with(module.as_str()))
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.level, |(_, level)| *level);
+        metadata.level() <= level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        if self.terminal {
+            let level = format!("{:5}", record.level());
+            eprintln!(
+                "{} {}: {}",
+                match record.level() {
+                    _ if !self.colour => level,
+                    Level::Error => level.red(),
+                    Level::Warn => level.yellow(),
+                    Level::Info => level.blue(),
+                    Level::Debug => level.green(),
+                    Level::Trace => level.grey(),
+                },
+                record.target(),
+                record.args()
+            );
+        }
+
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            let line = format!(
+                "{}.{:03} {:5} {}: {}\n",
+                time.as_secs(),
+                time.subsec_millis(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            if let Err(err) = file.write(line.as_bytes()) {
+                eprintln!("Failed to write to log file: {}", err);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: u32,
+    file: File,
+    len: u64,
+}
+
+impl RotatingFile {
+    fn open(config: &LogFile) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+        Ok(Self {
+            path: config.path.clone(),
+            max_bytes: config.max_bytes,
+            keep: config.keep,
+            len: file.metadata()?.len(),
+            file,
+        })
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.len + bytes.len() as u64 > self.max_bytes && self.len > 0 {
+            self.rotate()?;
+        }
+        self.file.write_all(bytes)?;
+        self.len += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let numbered = |i: u32| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{}", i));
+            PathBuf::from(path)
+        };
+        for i in (1..self.keep).rev() {
+            if numbered(i).exists() {
+                fs::rename(numbered(i), numbered(i + 1))?;
+            }
+        }
+        if self.keep > 0 {
+            fs::rename(&self.path, numbered(1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_directives_are_skipped() {
+        let config = LogConfig::default()
+            .with_filters("warn,engine::render=loud,nonsense,engine::audio=off");
+        assert_eq!(config.level, LevelFilter::Warn);
+        assert_eq!(
+            config.module_levels,
+            vec![("engine::audio".to_string(), LevelFilter::Off)]
+        );
+    }
+}
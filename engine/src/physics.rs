@@ -1,9 +1,9 @@
 use geo::{Contains, Point, Polygon};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::{cell::RefCell, f32::consts::PI, rc::Rc};
+use std::{cell::RefCell, f32::consts::PI, rc::Rc, time::Instant};
 use ultraviolet::{Lerp, Rotor2, Vec2};
 
-use crate::ShapeLiteral;
+use crate::{profiler, ShapeLiteral};
 
 type CollisionCallback<T> = Box<dyn Fn(&mut T, &T) -> CollisionResponse>;
 
@@ -22,10 +22,28 @@ pub struct PhysicsModule<T: Clone> {
     pub angular_velocity: f32,
     pub hitbox: ShapeLiteral,
     pub inner: T,
+    // position and rotation before the last step, `None` until the module has been stepped once
+    previous: Option<(Vec2, f32)>,
     on_collision: CollisionCallback<T>,
     current_collider: Option<Rc<RefCell<PhysicsModule<T>>>>,
 }
 
+impl<T: Clone> PhysicsModule<T> {
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        match self.previous {
+            Some((position, _)) => position.lerp(self.position, alpha),
+            None => self.position,
+        }
+    }
+
+    pub fn interpolated_rotation(&self, alpha: f32) -> f32 {
+        match self.previous {
+            Some((_, rotation)) => rotation.lerp(self.rotation, alpha),
+            None => self.rotation,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct PhysicsEngine<T: Clone> {
     modules: Vec<Rc<RefCell<PhysicsModule<T>>>>,
@@ -48,6 +66,7 @@ impl<T: Default + Clone> PhysicsEngine<T> {
             angular_velocity: 0.,
             hitbox,
             inner,
+            previous: None,
             on_collision: Box::new(on_collision),
             current_collider: None,
         }));
@@ -56,9 +75,11 @@ impl<T: Default + Clone> PhysicsEngine<T> {
     }
 
     pub fn update(&mut self, dt: f32) {
+        let start = Instant::now();
         self.modules.retain(|m| Rc::strong_count(m) > 1);
         for module_rc in &self.modules {
             let mut module = module_rc.borrow_mut();
+            module.previous = Some((module.position, module.rotation));
             let acceleration = module.force / module.mass;
             module.force = Vec2::zero();
             module.velocity += acceleration * dt;
@@ -118,6 +139,7 @@ impl<T: Default + Clone> PhysicsEngine<T> {
                 }
             }
         }
+        profiler::record("physics", start.elapsed());
     }
 }
 
@@ -0,0 +1,125 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use ultraviolet::{Vec2, Vec4};
+
+use crate::{
+    text::{TextBox, DEFAULT_FONT},
+    RenderLiteral,
+};
+
+// frames the overlay averages over
+const HISTORY_LEN: usize = 60;
+
+type Timings = Vec<(&'static str, Duration)>;
+
+#[derive(Default)]
+struct Profiler {
+    current: Timings,
+    history: VecDeque<Timings>,
+    frame: u64,
+    frame_start: Option<Instant>,
+    csv: Option<BufWriter<File>>,
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::default();
+}
+
+/// Adds `time` to the section `name` of the current frame.
+pub fn record(name: &'static str, time: Duration) {
+    PROFILER.with_borrow_mut(|p| match p.current.iter_mut().find(|(n, _)| *n == name) {
+        Some((_, total)) => *total += time,
+        None => p.current.push((name, time)),
+    });
+}
+
+pub fn time<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    let start = Instant::now();
+    let res = f();
+    record(name, start.elapsed());
+    res
+}
+
+/// Streams every following frame's timings to `path` as `frame,section,microseconds` rows.
+pub fn dump_csv(path: impl AsRef<Path>) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "frame,section,microseconds")?;
+    PROFILER.with_borrow_mut(|p| p.csv = Some(file));
+    Ok(())
+}
+
+/// Writes out the buffered csv rows, thread locals aren't reliably dropped when the process exits.
+pub fn flush() {
+    PROFILER.with_borrow_mut(|p| {
+        if let Some(Err(err)) = p.csv.as_mut().map(Write::flush) {
+            log::error!("Failed to write profiler csv: {}", err);
+        }
+    });
+}
+
+pub(crate) fn end_frame() {
+    PROFILER.with_borrow_mut(|p| {
+        let now = Instant::now();
+        if let Some(start) = p.frame_start.replace(now) {
+            p.current.push(("frame", now - start));
+        }
+
+        let timings = std::mem::take(&mut p.current);
+        if let Some(csv) = &mut p.csv {
+            let res = timings.iter().try_for_each(|(name, time)| {
+                writeln!(csv, "{},{},{}", p.frame, name, time.as_micros())
+            });
+            if let Err(err) = res {
+                log::error!("Failed to write profiler csv: {}", err);
+                p.csv = None;
+            }
+        }
+
+        if p.history.len() == HISTORY_LEN {
+            p.history.pop_front();
+        }
+        p.history.push_back(timings);
+        p.frame += 1;
+    });
+}
+
+pub(crate) fn overlay() -> Vec<RenderLiteral> {
+    let text = PROFILER.with_borrow(|p| {
+        let mut sums: Timings = vec![];
+        for (name, time) in p.history.iter().flatten() {
+            match sums.iter_mut().find(|(n, _)| n == name) {
+                Some((_, total)) => *total += *time,
+                None => sums.push((name, *time)),
+            }
+        }
+        sums.iter()
+            .map(|(name, total)| {
+                format!(
+                    "{}: {:.2} ms\n",
+                    name,
+                    total.as_secs_f64() * 1000. / p.history.len() as f64
+                )
+            })
+            .collect::<String>()
+    });
+
+    TextBox {
+        char_set: &DEFAULT_FONT,
+        string: &text,
+        pos: Vec2::zero(),
+        font_size: 4.,
+        space_width: 2.,
+        line_gap: 2.,
+        width: 10000.,
+        colour: Vec4::new(0., 1., 0., 1.),
+        ui_anchor: Some(Vec2::new(-0.95, -0.95)),
+    }
+    .laid_out()
+}
@@ -0,0 +1,79 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::mpsc::{channel, Sender},
+    thread::{self, JoinHandle},
+};
+
+use anyhow::Result;
+
+use crate::image::Image;
+
+/// Dumps rendered frames to `dir/frame-000000.png`, `dir/frame-000001.png`, ... While recording
+/// the game is updated exactly once per rendered frame, so the sequence plays back at the tick
+/// rate no matter how slow rendering and saving were, e.g. `ffmpeg -framerate 60 -i
+/// frame-%06d.png` for a video at a tick rate of 60 with `every_nth` of 1.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    pub dir: PathBuf,
+    /// Only every nth frame is saved, 1 saves all of them.
+    pub every_nth: u32,
+}
+
+pub(crate) struct Recorder {
+    every_nth: u32,
+    dir: PathBuf,
+    frame: u64,
+    saved: u64,
+    tx: Option<Sender<(PathBuf, Image)>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl Recorder {
+    pub(crate) fn new(recording: &Recording) -> Result<Self> {
+        fs::create_dir_all(&recording.dir)?;
+        // encoding is slower than rendering, so it gets its own thread
+        let (tx, rx) = channel::<(PathBuf, Image)>();
+        let writer = thread::spawn(move || {
+            for (path, image) in rx {
+                if let Err(err) = image.save_png(&path) {
+                    log::error!("Failed to save {}: {}", path.display(), err);
+                }
+            }
+        });
+        Ok(Self {
+            every_nth: recording.every_nth.max(1),
+            dir: recording.dir.clone(),
+            frame: 0,
+            saved: 0,
+            tx: Some(tx),
+            writer: Some(writer),
+        })
+    }
+
+    /// Counts a frame and returns whether it should be saved.
+    pub(crate) fn next_frame(&mut self) -> bool {
+        let save = self.frame.is_multiple_of(self.every_nth as u64);
+        self.frame += 1;
+        save
+    }
+
+    pub(crate) fn save(&mut self, image: Image) {
+        let path = self.dir.join(format!("frame-{:06}.png", self.saved));
+        self.saved += 1;
+        if let Err(err) = self.tx.as_ref().unwrap().send((path, image)) {
+            log::error!("Failed to queue recorded frame: {}", err);
+        }
+    }
+}
+
+impl Drop for Recorder {
+    // waits for the queued frames so none go missing on exit
+    fn drop(&mut self) {
+        drop(self.tx.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        log::info!("Recorded {} frames to {}", self.saved, self.dir.display());
+    }
+}
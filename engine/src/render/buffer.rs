@@ -100,6 +100,29 @@ impl<T> Buffer<T> {
         Ok(())
     }
 
+    pub fn copy_to_host(&self, ptr: *mut c_void, len: usize, ctx: &Context) -> Result<()> {
+        let Some(mapped) = self.mapped else {
+            bail!("Cannot read buffer from host");
+        };
+
+        unsafe {
+            if !self
+                .properties
+                .contains(vk::MemoryPropertyFlags::HOST_COHERENT)
+            {
+                ctx.device
+                    .invalidate_mapped_memory_ranges(&[vk::MappedMemoryRange::default()
+                        .memory(self.memory)
+                        .offset(0)
+                        .size(self.size)])?;
+            }
+
+            ptr.copy_from(mapped, len);
+        }
+
+        Ok(())
+    }
+
     // currently unused, but may come in handy
     #[allow(unused)]
     pub fn copy_to(
@@ -1,7 +1,7 @@
 use std::ffi::{c_void, CStr};
 
 use ash::{ext::debug_utils, prelude::VkResult, vk, Entry, Instance};
-use colorize::AnsiColor;
+use log::Level;
 
 #[cfg(not(debug_assertions))]
 pub const DEBUG_ENABLED: bool = false;
@@ -40,20 +40,14 @@ pub unsafe extern "system" fn vulkan_debug_callback(
         .to_str()
         .unwrap();
 
-    eprintln!(
-        "{}",
-        match message_severity {
-            vk::DebugUtilsMessageSeverityFlagsEXT::INFO =>
-                format!("INFO ({}): {}", type_str, mess_str).blue(),
-            vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE =>
-                format!("VERBOSE ({}): {}", type_str, mess_str).green(),
-            vk::DebugUtilsMessageSeverityFlagsEXT::WARNING =>
-                format!("WARNING ({}): {}", type_str, mess_str).yellow(),
-            vk::DebugUtilsMessageSeverityFlagsEXT::ERROR =>
-                format!("ERROR ({}): {}", type_str, mess_str).red(),
-            _ => unreachable!(),
-        }
-    );
+    let level = match message_severity {
+        vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE => Level::Trace,
+        vk::DebugUtilsMessageSeverityFlagsEXT::INFO => Level::Info,
+        vk::DebugUtilsMessageSeverityFlagsEXT::WARNING => Level::Warn,
+        vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => Level::Error,
+        _ => unreachable!(),
+    };
+    log::log!(level, "{}: {}", type_str, mess_str);
 
     vk::FALSE
 }
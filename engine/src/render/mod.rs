@@ -1,10 +1,6 @@
-use std::{
-    io::{stderr, Write},
-    mem::offset_of,
-    time::Instant,
-};
+use std::{mem::offset_of, time::Instant};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ash::{prelude::VkResult, vk};
 use bytemuck::{bytes_of, NoUninit};
 use context::Context;
@@ -12,23 +8,25 @@ use shaders::{
     GAME_VERTEX_SHADER, SIMPLE_GAME_FRAGMENT_SHADER, SIMPLE_UI_FRAGMENT_SHADER, UI_VERTEX_SHADER,
 };
 use ultraviolet::{Rotor2, Vec2, Vec4};
-use winit::window::Window;
+use winit::{dpi::PhysicalSize, window::Window};
 
 mod buffer;
 mod context;
 mod debug;
 mod pipeline;
 mod shaders;
+mod software;
 mod utils;
 
 use buffer::Buffer;
 use pipeline::{create_pipelines, Pipeline, PipelineCreateInfo};
+pub use software::SoftwareRenderer;
 use utils::{
     choose_swap_extent, choose_swap_present_mode, choose_swap_surface_format, QueueFamilyIndices,
     SwapchainSupportDetails,
 };
 
-use crate::text::Glyph;
+use crate::{image::Image, profiler, text::Glyph, Camera};
 
 #[derive(Debug, Clone)]
 pub enum RenderLiteral {
@@ -53,6 +51,19 @@ pub enum ShapeLiteral {
     },
 }
 
+/// Something that can draw an [`EverythingToDraw`], picked with
+/// [`EngineInitInfo::backend`](crate::EngineInitInfo::backend).
+pub trait RenderBackend {
+    fn init(&mut self, window: &Window) -> Result<()>;
+    /// Called whenever the window's inner size or scale factor changed.
+    fn resize(&mut self, size: PhysicalSize<u32>);
+    fn render(&mut self, to_draw: &EverythingToDraw, window: &Window) -> Result<()>;
+    /// Captures the next rendered frame for [`RenderBackend::take_screenshot`].
+    fn request_screenshot(&mut self);
+    /// The captured frame, or why it couldn't be captured.
+    fn take_screenshot(&mut self) -> Option<Result<Image>>;
+}
+
 // TODO: better name
 #[derive(Debug)]
 pub struct EverythingToDraw {
@@ -63,6 +74,14 @@ pub struct EverythingToDraw {
 }
 
 impl EverythingToDraw {
+    pub fn camera(&self, viewport: PhysicalSize<u32>) -> Camera {
+        Camera {
+            pos: self.camera_pos,
+            scale: self.scale,
+            viewport,
+        }
+    }
+
     fn game_pc(&self, window: &Window) -> GamePushConstants {
         let size = window.inner_size();
         GamePushConstants {
@@ -286,8 +305,9 @@ impl EverythingToDraw {
 
 const MAX_FRAMES_IN_FLIGHT: usize = 2;
 
+/// The default backend, presents to the window through a Vulkan swapchain.
 #[derive(Default)]
-pub struct Renderer {
+pub struct VulkanRenderer {
     ctx: Option<Context>,
 
     surface: vk::SurfaceKHR,
@@ -302,6 +322,8 @@ pub struct Renderer {
     swapchain_extent: vk::Extent2D,
     swapchain_image_views: Vec<vk::ImageView>,
     swapchain_framebuffers: Vec<vk::Framebuffer>,
+    // whether the swapchain images can be copied from, needed for screenshots
+    swapchain_transfer_src: bool,
 
     render_pass: vk::RenderPass,
     ui_polygon_pipeline: Pipeline,
@@ -320,12 +342,14 @@ pub struct Renderer {
     render_finished_semaphores: [vk::Semaphore; MAX_FRAMES_IN_FLIGHT],
 
     current_frame: usize,
-    pub resized: bool,
+    resized: bool,
+    capture: bool,
+    captured: Option<Result<Image>>,
     last_frame: Option<Instant>,
 }
 
-impl Renderer {
-    pub fn init(&mut self, window: &Window) -> Result<()> {
+impl RenderBackend for VulkanRenderer {
+    fn init(&mut self, window: &Window) -> Result<()> {
         self.ctx = Some(Context::new(window, &mut self.surface)?);
         self.create_queues();
         self.create_swapchain(window)?;
@@ -339,6 +363,25 @@ impl Renderer {
         Ok(())
     }
 
+    fn resize(&mut self, _size: PhysicalSize<u32>) {
+        // the swapchain takes its size from the window when it is recreated
+        self.resized = true;
+    }
+
+    fn render(&mut self, to_draw: &EverythingToDraw, window: &Window) -> Result<()> {
+        self.render_frame(to_draw, window)
+    }
+
+    fn request_screenshot(&mut self) {
+        self.capture = true;
+    }
+
+    fn take_screenshot(&mut self) -> Option<Result<Image>> {
+        self.captured.take()
+    }
+}
+
+impl VulkanRenderer {
     fn create_queues(&mut self) {
         unsafe {
             let ctx = self.ctx.as_ref().unwrap();
@@ -365,6 +408,16 @@ impl Renderer {
         let present_mode = choose_swap_present_mode(&details.present_modes);
         self.swapchain_extent = choose_swap_extent(&details.capabilities, window.inner_size());
 
+        self.swapchain_transfer_src = details
+            .capabilities
+            .supported_usage_flags
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC);
+        let image_usage = if self.swapchain_transfer_src {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC
+        } else {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+        };
+
         let mut image_count = details.capabilities.min_image_count + 1;
 
         if details.capabilities.max_image_count > 0
@@ -380,7 +433,7 @@ impl Renderer {
             .image_color_space(surface_format.color_space)
             .image_extent(self.swapchain_extent)
             .image_array_layers(1)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+            .image_usage(image_usage)
             .pre_transform(details.capabilities.current_transform)
             .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
             .present_mode(present_mode)
@@ -688,6 +741,15 @@ impl Renderer {
         )
     }
 
+    fn create_capture_buffer(&self) -> Result<Buffer<u8>> {
+        Buffer::new(
+            self.ctx.as_ref().unwrap(),
+            vk::BufferUsageFlags::TRANSFER_DST,
+            vk::MemoryPropertyFlags::HOST_VISIBLE,
+            capture_buffer_len(self.swapchain_extent),
+        )
+    }
+
     fn create_sync(&mut self) -> VkResult<()> {
         let ctx = self.ctx.as_ref().unwrap();
 
@@ -735,7 +797,80 @@ impl Renderer {
         self.create_framebuffers()
     }
 
-    pub fn render(&mut self, to_draw: &EverythingToDraw, window: &Window) -> Result<()> {
+    // the render pass leaves the image ready to present, so it is moved to a copyable layout
+    // and back around the copy
+    unsafe fn record_capture(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        buffer: &Buffer<u8>,
+    ) {
+        let ctx = self.ctx.as_ref().unwrap();
+        let subresource_range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+
+        ctx.device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[vk::ImageMemoryBarrier::default()
+                .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+                .old_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+                .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(image)
+                .subresource_range(subresource_range)],
+        );
+
+        ctx.device.cmd_copy_image_to_buffer(
+            command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            buffer.buffer,
+            &[vk::BufferImageCopy::default()
+                .image_subresource(vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: 0,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                })
+                .image_extent(vk::Extent3D {
+                    width: self.swapchain_extent.width,
+                    height: self.swapchain_extent.height,
+                    depth: 1,
+                })],
+        );
+
+        ctx.device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[vk::ImageMemoryBarrier::default()
+                .src_access_mask(vk::AccessFlags::TRANSFER_READ)
+                .dst_access_mask(vk::AccessFlags::NONE)
+                .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                .new_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(image)
+                .subresource_range(subresource_range)],
+        );
+    }
+
+    fn render_frame(&mut self, to_draw: &EverythingToDraw, window: &Window) -> Result<()> {
         let command_buffer = self.command_buffers[self.current_frame];
         let fence = self.fences[self.current_frame];
         let image_available_semaphore = self.image_available_semaphores[self.current_frame];
@@ -750,7 +885,7 @@ impl Renderer {
             if self.resized {
                 self.resized = false;
                 self.recreate_swapchain(window)?;
-                return self.render(to_draw, window);
+                return self.render_frame(to_draw, window);
             } else {
                 match ctx.swapchain_device.acquire_next_image(
                     self.swapchain,
@@ -761,7 +896,7 @@ impl Renderer {
                     Ok((i, _)) => i,
                     Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
                         self.recreate_swapchain(window)?;
-                        return self.render(to_draw, window);
+                        return self.render_frame(to_draw, window);
                     }
                     other => other?.0,
                 }
@@ -770,18 +905,26 @@ impl Renderer {
 
         #[cfg(debug_assertions)]
         if let Some(last_frame) = self.last_frame.replace(Instant::now()) {
-            eprint!("\rFPS: {:.2}", last_frame.elapsed().as_secs_f32().recip());
-            stderr().flush().unwrap();
+            log::trace!("FPS: {:.2}", last_frame.elapsed().as_secs_f32().recip());
         }
 
         let game_pc = to_draw.game_pc(window);
         let ui_pc = to_draw.ui_pc(window);
         let frag_pc = to_draw.frag_pc();
-        let (game_vertices, game_polygon_vertex_count) = to_draw.game_vertices();
-        let (ui_vertices, ui_polygon_vertex_count) = to_draw.ui_vertices();
-        let (indices, ui_start) = to_draw.indices();
+        let (
+            (game_vertices, game_polygon_vertex_count),
+            (ui_vertices, ui_polygon_vertex_count),
+            (indices, ui_start),
+        ) = profiler::time("vertices", || {
+            (
+                to_draw.game_vertices(),
+                to_draw.ui_vertices(),
+                to_draw.indices(),
+            )
+        });
         let bg_colour = to_draw.inverted as u8 as f32;
 
+        let upload_start = Instant::now();
         if game_vertices.len() > self.game_vb.len {
             self.game_vb = self.create_vertex_buffer::<GameVertex>(game_vertices.len())?;
         }
@@ -806,6 +949,22 @@ impl Renderer {
             indices.len() * size_of::<u16>(),
             ctx,
         )?;
+        profiler::record("upload", upload_start.elapsed());
+
+        let capture_buffer = match std::mem::take(&mut self.capture) {
+            true if !self.swapchain_transfer_src => {
+                log::warn!("The swapchain doesn't support screenshots");
+                None
+            }
+            true => match capture_swaps_red_blue(self.swapchain_image_format) {
+                Ok(_) => Some(self.create_capture_buffer()?),
+                Err(err) => {
+                    self.captured = Some(Err(err));
+                    None
+                }
+            },
+            false => None,
+        };
 
         unsafe {
             ctx.device.reset_fences(&[fence]).unwrap();
@@ -951,10 +1110,19 @@ impl Renderer {
 
             ctx.device.cmd_end_render_pass(command_buffer);
 
+            if let Some(capture_buffer) = &capture_buffer {
+                self.record_capture(
+                    command_buffer,
+                    self.swapchain_images[image_index as usize],
+                    capture_buffer,
+                );
+            }
+
             ctx.device.end_command_buffer(command_buffer).unwrap();
 
             let signal_semaphores = [render_finished_semaphore];
 
+            let submit_start = Instant::now();
             ctx.device.queue_submit(
                 self.graphics_queue,
                 &[vk::SubmitInfo::default()
@@ -972,6 +1140,30 @@ impl Renderer {
                     .swapchains(&[self.swapchain])
                     .image_indices(&[image_index]),
             )?;
+            profiler::record("submit", submit_start.elapsed());
+        }
+
+        if let Some(capture_buffer) = capture_buffer {
+            unsafe {
+                ctx.device.wait_for_fences(&[fence], true, u64::MAX)?;
+            }
+            let mut pixels = vec![0; capture_buffer_len(self.swapchain_extent)];
+            let res = capture_buffer.copy_to_host(pixels.as_mut_ptr() as _, pixels.len(), ctx);
+            capture_buffer.free(ctx);
+            res?;
+            let swap = capture_swaps_red_blue(self.swapchain_image_format)?;
+            pixels.chunks_exact_mut(4).for_each(|px| {
+                if swap {
+                    px.swap(0, 2);
+                }
+                // the swapchain's alpha is whatever was blended last, the player sees it opaque
+                px[3] = 255;
+            });
+            self.captured = Some(Ok(Image {
+                width: self.swapchain_extent.width,
+                height: self.swapchain_extent.height,
+                pixels,
+            }));
         }
 
         self.current_frame = (self.current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
@@ -979,9 +1171,26 @@ impl Renderer {
     }
 }
 
-impl Drop for Renderer {
+// only 8 bit rgba and bgra swapchains can be captured, which is what `choose_swap_surface_format`
+// prefers
+fn capture_buffer_len(extent: vk::Extent2D) -> usize {
+    (extent.width * extent.height * 4) as _
+}
+
+fn capture_swaps_red_blue(format: vk::Format) -> Result<bool> {
+    match format {
+        vk::Format::B8G8R8A8_SRGB | vk::Format::B8G8R8A8_UNORM => Ok(true),
+        vk::Format::R8G8B8A8_SRGB | vk::Format::R8G8B8A8_UNORM => Ok(false),
+        format => Err(anyhow!("Can't capture a {:?} swapchain", format)),
+    }
+}
+
+impl Drop for VulkanRenderer {
     fn drop(&mut self) {
-        let ctx = self.ctx.as_ref().unwrap();
+        // never initialised, e.g. when the game only ran headless
+        let Some(ctx) = self.ctx.as_ref() else {
+            return;
+        };
 
         unsafe {
             ctx.device.device_wait_idle().unwrap();
@@ -0,0 +1,288 @@
+use anyhow::Result;
+use ultraviolet::{Vec2, Vec4};
+use winit::{dpi::PhysicalSize, window::Window};
+
+use super::{EverythingToDraw, GameVertex, RenderBackend, UiVertex};
+use crate::image::Image;
+
+/// Draws the same [`EverythingToDraw`] as the Vulkan renderer on the CPU, for machines without
+/// a GPU. Follows the shaders' transforms, point sizes and blending, so the output only differs
+/// in how the hardware rounds lines and points. As a backend it doesn't present anything, frames
+/// are only kept for screenshots.
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    // linear colours, encoded to srgb like the swapchain does when read back
+    pixels: Vec<Vec4>,
+    capture: bool,
+    captured: Option<Result<Image>>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Vec4::zero(); (width * height) as usize],
+            capture: false,
+            captured: None,
+        }
+    }
+
+    pub fn rasterize(&mut self, to_draw: &EverythingToDraw) {
+        let bg_colour = to_draw.inverted as u8 as f32;
+        self.pixels
+            .fill(Vec4::new(bg_colour, bg_colour, bg_colour, 1.));
+
+        let (game_vertices, game_polygon_vertex_count) = to_draw.game_vertices();
+        let (ui_vertices, ui_polygon_vertex_count) = to_draw.ui_vertices();
+        let (indices, ui_start) = to_draw.indices();
+        let (width, height) = (self.width as f32, self.height as f32);
+        let fragment = |colour: Vec4| {
+            if to_draw.inverted {
+                Vec4::new(1. - colour.x, 1. - colour.y, 1. - colour.z, colour.w)
+            } else {
+                colour
+            }
+        };
+
+        // same order as the draw calls in `VulkanRenderer::render_frame`
+        let game = |v: &GameVertex| {
+            let mut pos = v.position - to_draw.camera_pos;
+            pos.x *= to_draw.scale / width;
+            pos.y *= to_draw.scale / height;
+            pos
+        };
+        for line in indices[..ui_start].chunks_exact(2) {
+            let (a, b) = (
+                &game_vertices[line[0] as usize],
+                &game_vertices[line[1] as usize],
+            );
+            self.line(game(a), game(b), fragment(a.colour));
+        }
+        for v in &game_vertices[game_polygon_vertex_count..] {
+            self.point(
+                game(v),
+                v.point_size / 2. * to_draw.scale,
+                fragment(v.colour),
+            );
+        }
+
+        let ui = |v: &UiVertex| v.anchor + Vec2::new(v.position.x / width, v.position.y / height);
+        for line in indices[ui_start..].chunks_exact(2) {
+            let (a, b) = (
+                &ui_vertices[line[0] as usize],
+                &ui_vertices[line[1] as usize],
+            );
+            self.line(ui(a), ui(b), fragment(a.colour));
+        }
+        for v in &ui_vertices[ui_polygon_vertex_count..] {
+            self.point(ui(v), v.point_size / 2., fragment(v.colour));
+        }
+    }
+
+    pub fn image(&self) -> Image {
+        let encode = |c: f32| {
+            let c = c.clamp(0., 1.);
+            let srgb = if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1. / 2.4) - 0.055
+            };
+            (srgb * 255. + 0.5) as u8
+        };
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .pixels
+                .iter()
+                .flat_map(|p| {
+                    [
+                        encode(p.x),
+                        encode(p.y),
+                        encode(p.z),
+                        // blending leaves the last source's alpha, but the frame is shown opaque
+                        255,
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    fn to_pixels(&self, ndc: Vec2) -> Vec2 {
+        Vec2::new(
+            (ndc.x + 1.) / 2. * self.width as f32,
+            (ndc.y + 1.) / 2. * self.height as f32,
+        )
+    }
+
+    // src alpha blending with the alpha taken straight from the source, like the pipelines
+    fn blend(&mut self, x: i64, y: i64, colour: Vec4) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let dst = &mut self.pixels[(y * self.width as i64 + x) as usize];
+        let blended = colour * colour.w + *dst * (1. - colour.w);
+        *dst = Vec4::new(blended.x, blended.y, blended.z, colour.w);
+    }
+
+    // one pixel per column (or row for steep lines) whose centre the line crosses, the end
+    // pixel is left out so the shared corners of a polygon aren't blended twice
+    fn line(&mut self, a: Vec2, b: Vec2, colour: Vec4) {
+        let (a, b) = (self.to_pixels(a), self.to_pixels(b));
+        let d = b - a;
+        let steep = d.y.abs() > d.x.abs();
+        let (major, major_d) = if steep { (a.y, d.y) } else { (a.x, d.x) };
+        if major_d == 0. {
+            return;
+        }
+        let (start, end) = if major_d > 0. {
+            ((major - 0.5).ceil(), (major + major_d - 0.5).ceil())
+        } else {
+            ((major + major_d + 0.5).floor(), (major + 0.5).floor())
+        };
+        for i in start as i64..end as i64 {
+            let t = (i as f32 + 0.5 - major) / major_d;
+            let p = a + d * t;
+            if steep {
+                self.blend(p.x.floor() as i64, i, colour);
+            } else {
+                self.blend(i, p.y.floor() as i64, colour);
+            }
+        }
+    }
+
+    // a square of `size` pixels centred on the point, at least one pixel big
+    fn point(&mut self, centre: Vec2, size: f32, colour: Vec4) {
+        let centre = self.to_pixels(centre);
+        let half = size.max(1.) / 2.;
+        let (x0, x1) = (
+            (centre.x - half - 0.5).ceil(),
+            (centre.x + half - 0.5).ceil(),
+        );
+        let (y0, y1) = (
+            (centre.y - half - 0.5).ceil(),
+            (centre.y + half - 0.5).ceil(),
+        );
+        for y in y0 as i64..y1 as i64 {
+            for x in x0 as i64..x1 as i64 {
+                self.blend(x, y, colour);
+            }
+        }
+    }
+}
+
+impl RenderBackend for SoftwareRenderer {
+    fn init(&mut self, window: &Window) -> Result<()> {
+        self.resize(window.inner_size());
+        Ok(())
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        *self = Self::new(size.width, size.height);
+    }
+
+    fn render(&mut self, to_draw: &EverythingToDraw, _window: &Window) -> Result<()> {
+        self.rasterize(to_draw);
+        if std::mem::take(&mut self.capture) {
+            self.captured = Some(Ok(self.image()));
+        }
+        Ok(())
+    }
+
+    fn request_screenshot(&mut self) {
+        self.capture = true;
+    }
+
+    fn take_screenshot(&mut self) -> Option<Result<Image>> {
+        self.captured.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    use super::*;
+    use crate::{RenderLiteral, ShapeLiteral};
+
+    const RED: Vec4 = Vec4::new(1., 0., 0., 1.);
+
+    // 100x100 pixels with a world unit per pixel, the origin in the middle of pixel (50, 50)
+    fn draw(inverted: bool, shapes: Vec<RenderLiteral>) -> Image {
+        let mut renderer = SoftwareRenderer::new(100, 100);
+        renderer.rasterize(&EverythingToDraw {
+            camera_pos: Vec2::new(-0.5, -0.5),
+            scale: 2.,
+            inverted,
+            shapes,
+        });
+        renderer.image()
+    }
+
+    fn pixel(image: &Image, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * image.width + x) * 4) as usize;
+        image.pixels[i..i + 4].try_into().unwrap()
+    }
+
+    // a 40x40 outline from (30, 30) to (70, 70)
+    fn square(colour: Vec4) -> RenderLiteral {
+        RenderLiteral::Game(ShapeLiteral::Polygon {
+            pos: Vec2::zero(),
+            colour,
+            angles: (0..4).map(|i| FRAC_PI_4 + i as f32 * FRAC_PI_2).collect(),
+            distances: vec![20. * 2f32.sqrt(); 4],
+            border_thickness: 1.,
+        })
+    }
+
+    #[test]
+    fn polygon_outline() {
+        let image = draw(false, vec![square(RED)]);
+        assert_eq!(pixel(&image, 50, 70), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 30, 50), [255, 0, 0, 255]);
+        assert_eq!(
+            pixel(&image, 50, 50),
+            [0, 0, 0, 255],
+            "only the outline is drawn"
+        );
+        assert_eq!(pixel(&image, 10, 10), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn translucent_pixels_are_opaque() {
+        let image = draw(false, vec![square(Vec4::new(1., 0., 0., 0.5))]);
+        // half of linear red over black, encoded to srgb
+        assert_eq!(pixel(&image, 50, 70), [188, 0, 0, 255]);
+    }
+
+    #[test]
+    fn glyph_points() {
+        let mut glyph = [[false; 5]; 5];
+        glyph[0][0] = true;
+        glyph[0][2] = true;
+        let image = draw(
+            false,
+            vec![RenderLiteral::Game(ShapeLiteral::Glyph {
+                pos: Vec2::zero(),
+                colour: RED,
+                glyph,
+                size: 4.,
+            })],
+        );
+        // 4 pixel squares centred on (50, 50) and (58, 50)
+        for x in (48..52).chain(56..60) {
+            assert_eq!(pixel(&image, x, 50), [255, 0, 0, 255], "x = {}", x);
+        }
+        assert_eq!(pixel(&image, 53, 50), [0, 0, 0, 255]);
+        assert_eq!(pixel(&image, 50, 53), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn inverted_colours() {
+        let image = draw(true, vec![square(RED)]);
+        assert_eq!(pixel(&image, 50, 70), [0, 255, 255, 255]);
+        assert_eq!(pixel(&image, 50, 50), [255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, LineWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use ultraviolet::Vec2;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, TouchPhase},
+    keyboard::{Key, ModifiersState, SmolStr},
+};
+
+use crate::{
+    input::{Binding, GamepadAxis, Input},
+    Lifecycle,
+};
+
+/// What to do with the player's inputs, set through [`crate::EngineInitInfo::replay`].
+pub enum Replay {
+    /// Writes every input and window change with the tick it arrived before to `path`, along with
+    /// the seed the game derives its randomness from.
+    Record {
+        path: PathBuf,
+        seed: u64,
+        seed_override: Option<u64>,
+    },
+    /// Feeds the logged events to the game at the same ticks, ignoring the real ones until the
+    /// log runs out.
+    Play(InputLog),
+}
+
+/// A recorded session, one `<tick> <event>` per line after a `seed <seed>` line, e.g.
+/// `120 press w`, `121 cursor 640 360`, `300 release PadSouth 0`, `301 axis 0 LeftStickX 0.5` or
+/// `302 text 104 105` with the typed characters as code points, `303 wheel 0 -1`,
+/// `304 touch 1 Started 200 300`, `305 modifiers 4` with the bits of [`ModifiersState`],
+/// `306 lifecycle FocusLost` or `307 lifecycle Resized 800 600`. A `seed_override <seed>` line
+/// can follow the seed.
+#[derive(Clone, Debug, Default)]
+pub struct InputLog {
+    pub seed: u64,
+    /// A seed the game was told to use instead of drawing its own from `seed`, e.g. through an
+    /// environment variable, kept so the replay doesn't depend on it being set again.
+    pub seed_override: Option<u64>,
+    pub events: Vec<(u64, LoggedEvent)>,
+}
+
+/// Window changes are logged along with the input, since scenes react to them too, e.g. by
+/// pausing when focus is lost.
+#[derive(Clone, Debug)]
+pub enum LoggedEvent {
+    Input(Input),
+    Lifecycle(Lifecycle),
+}
+
+impl InputLog {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut log = Self::default();
+        for (i, line) in text.lines().map(str::trim).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(seed) = line.strip_prefix("seed ") {
+                log.seed = seed.trim().parse()?;
+                continue;
+            }
+            if let Some(seed) = line.strip_prefix("seed_override ") {
+                log.seed_override = Some(seed.trim().parse()?);
+                continue;
+            }
+            match parse_event(line) {
+                Some(event) => log.events.push(event),
+                // a key we can't name shouldn't throw away the rest of the session
+                None => log::warn!("{}:{}: can't replay `{}`", path.display(), i + 1, line),
+            }
+        }
+        Ok(log)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut text = format!("seed {}\n", self.seed);
+        if let Some(seed) = self.seed_override {
+            let _ = writeln!(text, "seed_override {}", seed);
+        }
+        for (tick, event) in &self.events {
+            let _ = writeln!(text, "{} {}", tick, format_event(event));
+        }
+        fs::write(path, text)
+    }
+
+    /// The tick after the last input, where playback runs out.
+    pub fn len(&self) -> u64 {
+        self.events.last().map_or(0, |(tick, _)| tick + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+fn format_event(event: &LoggedEvent) -> String {
+    match event {
+        LoggedEvent::Input(input) => format_input(input),
+        LoggedEvent::Lifecycle(Lifecycle::Resized(size)) => {
+            format!("lifecycle Resized {} {}", size.width, size.height)
+        }
+        LoggedEvent::Lifecycle(Lifecycle::ScaleFactorChanged(scale)) => {
+            format!("lifecycle ScaleFactorChanged {}", scale)
+        }
+        LoggedEvent::Lifecycle(event) => format!("lifecycle {:?}", event),
+    }
+}
+
+fn format_input(input: &Input) -> String {
+    let state = |state: &ElementState| match state {
+        ElementState::Pressed => "press",
+        ElementState::Released => "release",
+    };
+    match input {
+        Input::Keyboard { key, state: s } => {
+            let key = match key {
+                // written as is so shift still shows up in the replay
+                Key::Character(c) => c.to_string(),
+                key => Binding::Key(key.clone()).to_string(),
+            };
+            format!("{} {}", state(s), key)
+        }
+        Input::Cursor(pos) => format!("cursor {} {}", pos.x, pos.y),
+        Input::MouseButton { btn, state: s } => {
+            format!("{} {}", state(s), Binding::Mouse(*btn))
+        }
+        Input::GamepadButton {
+            gamepad,
+            btn,
+            state: s,
+        } => format!("{} {} {}", state(s), Binding::Gamepad(*btn), gamepad),
+        Input::GamepadAxis {
+            gamepad,
+            axis,
+            value,
+        } => format!("axis {} {:?} {}", gamepad, axis, value),
+        Input::MouseWheel(delta) => format!("wheel {} {}", delta.x, delta.y),
+        Input::Touch { id, phase, pos } => {
+            format!("touch {} {:?} {} {}", id, phase, pos.x, pos.y)
+        }
+        Input::Modifiers(modifiers) => format!("modifiers {}", modifiers.bits()),
+        Input::Text(text) => text
+            .chars()
+            .fold("text".to_string(), |s, c| format!("{} {}", s, c as u32)),
+    }
+}
+
+fn parse_event(line: &str) -> Option<(u64, LoggedEvent)> {
+    let mut words = line.split_whitespace();
+    let tick = words.next()?.parse().ok()?;
+    let input = match words.next()? {
+        "lifecycle" => {
+            let event = match words.next()? {
+                "FocusGained" => Lifecycle::FocusGained,
+                "FocusLost" => Lifecycle::FocusLost,
+                "Resized" => Lifecycle::Resized(PhysicalSize::new(
+                    words.next()?.parse().ok()?,
+                    words.next()?.parse().ok()?,
+                )),
+                "ScaleFactorChanged" => Lifecycle::ScaleFactorChanged(words.next()?.parse().ok()?),
+                "Minimised" => Lifecycle::Minimised,
+                "Restored" => Lifecycle::Restored,
+                _ => return None,
+            };
+            return Some((tick, LoggedEvent::Lifecycle(event)));
+        }
+        kind @ ("press" | "release") => {
+            let state = if kind == "press" {
+                ElementState::Pressed
+            } else {
+                ElementState::Released
+            };
+            let name = words.next()?;
+            if name.chars().count() == 1 {
+                Input::Keyboard {
+                    key: Key::Character(SmolStr::new(name)),
+                    state,
+                }
+            } else {
+                match name.parse().ok()? {
+                    Binding::Key(key) => Input::Keyboard { key, state },
+                    Binding::Mouse(btn) => Input::MouseButton { btn, state },
+                    Binding::Gamepad(btn) => Input::GamepadButton {
+                        gamepad: words.next()?.parse().ok()?,
+                        btn,
+                        state,
+                    },
+                    Binding::Axis { .. } | Binding::Shortcut { .. } => return None,
+                }
+            }
+        }
+        "cursor" => Input::Cursor(PhysicalPosition::new(
+            words.next()?.parse().ok()?,
+            words.next()?.parse().ok()?,
+        )),
+        "axis" => {
+            let gamepad = words.next()?.parse().ok()?;
+            let axis = words.next()?;
+            Input::GamepadAxis {
+                gamepad,
+                axis: *GamepadAxis::ALL
+                    .iter()
+                    .find(|a| format!("{:?}", a) == axis)?,
+                value: words.next()?.parse().ok()?,
+            }
+        }
+        "wheel" => Input::MouseWheel(Vec2::new(
+            words.next()?.parse().ok()?,
+            words.next()?.parse().ok()?,
+        )),
+        "touch" => Input::Touch {
+            id: words.next()?.parse().ok()?,
+            phase: match words.next()? {
+                "Started" => TouchPhase::Started,
+                "Moved" => TouchPhase::Moved,
+                "Ended" => TouchPhase::Ended,
+                "Cancelled" => TouchPhase::Cancelled,
+                _ => return None,
+            },
+            pos: PhysicalPosition::new(words.next()?.parse().ok()?, words.next()?.parse().ok()?),
+        },
+        "modifiers" => Input::Modifiers(ModifiersState::from_bits_truncate(
+            words.next()?.parse().ok()?,
+        )),
+        "text" => Input::Text(
+            words
+                .map(|c| char::from_u32(c.parse().ok()?))
+                .collect::<Option<_>>()?,
+        ),
+        _ => return None,
+    };
+    Some((tick, LoggedEvent::Input(input)))
+}
+
+/// Appends inputs to the log file as they come in, so a crash still leaves everything up to it.
+pub(crate) struct InputRecorder {
+    file: LineWriter<File>,
+}
+
+impl InputRecorder {
+    pub(crate) fn new(path: &Path, seed: u64, seed_override: Option<u64>) -> Result<Self> {
+        let mut file = LineWriter::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        writeln!(file, "seed {}", seed)?;
+        if let Some(seed) = seed_override {
+            writeln!(file, "seed_override {}", seed)?;
+        }
+        Ok(Self { file })
+    }
+
+    pub(crate) fn record(&mut self, tick: u64, event: &LoggedEvent) {
+        if let Err(err) = writeln!(self.file, "{} {}", tick, format_event(event)) {
+            log::error!("Failed to write input log: {}", err);
+        }
+    }
+}
+
+pub(crate) struct InputPlayer {
+    events: VecDeque<(u64, LoggedEvent)>,
+}
+
+impl InputPlayer {
+    pub(crate) fn new(log: InputLog) -> Self {
+        Self {
+            events: log.events.into(),
+        }
+    }
+
+    /// The events that arrived before update number `tick`.
+    pub(crate) fn due(&mut self, tick: u64) -> Vec<LoggedEvent> {
+        let mut due = vec![];
+        while self.events.front().is_some_and(|(t, _)| *t <= tick) {
+            due.push(self.events.pop_front().unwrap().1);
+        }
+        due
+    }
+
+    pub(crate) fn finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_round_trip() {
+        let events = [
+            LoggedEvent::Input(Input::Keyboard {
+                key: Key::Character("W".into()),
+                state: ElementState::Pressed,
+            }),
+            LoggedEvent::Input(Input::Text("hé".into())),
+            LoggedEvent::Lifecycle(Lifecycle::FocusLost),
+            LoggedEvent::Lifecycle(Lifecycle::Resized(PhysicalSize::new(800, 600))),
+            LoggedEvent::Lifecycle(Lifecycle::ScaleFactorChanged(1.5)),
+            LoggedEvent::Lifecycle(Lifecycle::Minimised),
+        ];
+        for (tick, event) in events.into_iter().enumerate() {
+            let line = format!("{} {}", tick, format_event(&event));
+            let (parsed_tick, parsed) = parse_event(&line).unwrap();
+            assert_eq!(parsed_tick, tick as u64);
+            assert_eq!(format!("{:?}", parsed), format!("{:?}", event), "{}", line);
+        }
+    }
+}
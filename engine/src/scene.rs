@@ -0,0 +1,85 @@
+use crate::{input::InputState, EverythingToDraw, Input, Lifecycle};
+
+pub enum SceneAction<C> {
+    None,
+    Push(Box<dyn Scene<C>>),
+    Pop,
+    Replace(Box<dyn Scene<C>>),
+    /// Drops every scene on the stack and starts over from the given one.
+    Reset(Box<dyn Scene<C>>),
+}
+
+/// One screen of a game, `C` is whatever state the scenes share.
+pub trait Scene<C> {
+    fn update(&mut self, _ctx: &mut C, _dt: f32, _input: &InputState) -> SceneAction<C> {
+        SceneAction::None
+    }
+    fn input(&mut self, ctx: &mut C, input: Input) -> SceneAction<C>;
+    fn draw(&self, ctx: &C, alpha: f32) -> EverythingToDraw;
+    fn lifecycle(&mut self, _ctx: &mut C, _event: Lifecycle) -> SceneAction<C> {
+        SceneAction::None
+    }
+    /// Whether the scene below keeps being drawn underneath this one.
+    fn draws_below(&self) -> bool {
+        false
+    }
+}
+
+/// Only the top scene gets updates, input and lifecycle events.
+pub struct SceneStack<C> {
+    scenes: Vec<Box<dyn Scene<C>>>,
+}
+
+impl<C> SceneStack<C> {
+    pub fn new(root: Box<dyn Scene<C>>) -> Self {
+        Self { scenes: vec![root] }
+    }
+
+    /// How many scenes are stacked, 1 when only the root is left.
+    pub fn depth(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn update(&mut self, ctx: &mut C, dt: f32, input: &InputState) {
+        let action = self.scenes.last_mut().unwrap().update(ctx, dt, input);
+        self.apply(action);
+    }
+
+    pub fn input(&mut self, ctx: &mut C, input: Input) {
+        let action = self.scenes.last_mut().unwrap().input(ctx, input);
+        self.apply(action);
+    }
+
+    pub fn lifecycle(&mut self, ctx: &mut C, event: Lifecycle) {
+        let action = self.scenes.last_mut().unwrap().lifecycle(ctx, event);
+        self.apply(action);
+    }
+
+    /// Camera, scale and inversion come from the lowest scene that is drawn,
+    /// the scenes above only add their shapes on top.
+    pub fn draw(&self, ctx: &C, alpha: f32) -> EverythingToDraw {
+        let bottom = self
+            .scenes
+            .iter()
+            .rposition(|s| !s.draws_below())
+            .unwrap_or(0);
+        let mut scenes = self.scenes[bottom..].iter();
+        let mut to_draw = scenes.next().unwrap().draw(ctx, alpha);
+        for scene in scenes {
+            to_draw.shapes.append(&mut scene.draw(ctx, alpha).shapes);
+        }
+        to_draw
+    }
+
+    pub fn apply(&mut self, action: SceneAction<C>) {
+        match action {
+            SceneAction::None => (),
+            SceneAction::Push(scene) => self.scenes.push(scene),
+            // the root scene stays, there would be nothing to draw otherwise
+            SceneAction::Pop if self.scenes.len() > 1 => drop(self.scenes.pop()),
+            SceneAction::Pop => (),
+            SceneAction::Replace(scene) => *self.scenes.last_mut().unwrap() = scene,
+            SceneAction::Reset(scene) => self.scenes = vec![scene],
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use ash::vk::SubmitFlags;
 use phf::phf_map;
 use ultraviolet::{Vec2, Vec4};
+use winit::{
+    event::ElementState,
+    keyboard::{Key, NamedKey},
+};
 
-use crate::{Game, RenderLiteral, ShapeLiteral};
+use crate::{Game, Input, RenderLiteral, ShapeLiteral};
 
 pub type Glyph = [[bool; 5]; 5];
 
@@ -36,9 +40,15 @@ pub struct TextBox<'a> {
 
 impl TextBox<'_> {
     pub fn laid_out(&self) -> Vec<RenderLiteral> {
+        self.layout().0
+    }
+
+    // also returns where the last word ended, relative to `pos` and in units of `font_size`
+    fn layout(&self) -> (Vec<RenderLiteral>, Vec2) {
         let mut render_literals = vec![];
         let mut cur_line_width = 0.;
         let mut cur_line = 0;
+        let mut end = Vec2::zero();
         for line in self.string.lines() {
             for word in line.split_whitespace() {
                 let word_len = word
@@ -71,6 +81,7 @@ impl TextBox<'_> {
                     });
                     cur_line_width += c.width;
                 }
+                end = Vec2::new(cur_line_width, cur_line as f32 * (5. + self.line_gap));
                 cur_line_width += self.space_width;
                 if cur_line_width > self.width {
                     cur_line += 1;
@@ -81,6 +92,77 @@ impl TextBox<'_> {
             cur_line += 1;
         }
 
+        (render_literals, end)
+    }
+}
+
+/// A single line the player types into, e.g. a name. Only takes characters `char_set` can draw.
+pub struct TextField {
+    pub text: String,
+    pub max_len: usize,
+    pub char_set: &'static dyn CharSet,
+}
+
+impl TextField {
+    pub fn new(char_set: &'static dyn CharSet, max_len: usize) -> Self {
+        Self {
+            text: String::new(),
+            max_len,
+            char_set,
+        }
+    }
+
+    /// Returns the text when enter is pressed.
+    pub fn input(&mut self, input: &Input) -> Option<String> {
+        match input {
+            Input::Text(text) => {
+                for c in text.chars() {
+                    // [`TextBox`] collapses runs of spaces, so they aren't taken in the first place
+                    let accepted = match c {
+                        ' ' => !self.text.is_empty() && !self.text.ends_with(' '),
+                        c => self.char_set.get_char(c).is_some(),
+                    };
+                    if accepted && self.text.chars().count() < self.max_len {
+                        self.text.push(c);
+                    }
+                }
+            }
+            Input::Keyboard {
+                key: Key::Named(NamedKey::Backspace),
+                state: ElementState::Pressed,
+            } => drop(self.text.pop()),
+            Input::Keyboard {
+                key: Key::Named(NamedKey::Enter),
+                state: ElementState::Pressed,
+            } => return Some(self.text.clone()),
+            _ => (),
+        }
+        None
+    }
+
+    /// The text followed by a caret, laid out like `style` apart from its string and char set.
+    pub fn laid_out(&self, style: TextBox) -> Vec<RenderLiteral> {
+        let (mut render_literals, mut caret) = TextBox {
+            string: &self.text,
+            char_set: self.char_set,
+            ..style
+        }
+        .layout();
+        if self.text.ends_with(' ') {
+            caret.x += style.space_width;
+        }
+        let mut glyph = [[false; 5]; 5];
+        glyph[4] = [true, true, true, false, false];
+        let shape = ShapeLiteral::Glyph {
+            pos: style.pos + caret * style.font_size,
+            colour: style.colour,
+            glyph,
+            size: style.font_size,
+        };
+        render_literals.push(match style.ui_anchor {
+            Some(anchor) => RenderLiteral::UI { anchor, shape },
+            None => RenderLiteral::Game(shape),
+        });
         render_literals
     }
 }
@@ -807,3 +889,41 @@ pub static DEFAULT_FONT: phf::Map<char, Character> = phf_map! {
         width: 5.5,
     },
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(literal: &RenderLiteral) -> Vec2 {
+        match literal {
+            RenderLiteral::Game(ShapeLiteral::Glyph { pos, .. }) => *pos,
+            other => panic!("not a glyph: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn caret_follows_wrapped_text() {
+        let mut field = TextField::new(&DEFAULT_FONT, 20);
+        field.input(&Input::Text("  AB   CD ".to_string()));
+        assert_eq!(field.text, "AB CD ");
+
+        let literals = field.laid_out(TextBox {
+            char_set: &DEFAULT_FONT,
+            string: "",
+            pos: Vec2::new(100., 50.),
+            font_size: 2.,
+            space_width: 2.,
+            line_gap: 1.,
+            width: 20.,
+            colour: Vec4::one(),
+            ui_anchor: None,
+        });
+        let [.., d, caret] = literals.as_slice() else {
+            panic!("nothing laid out");
+        };
+        // `CD` doesn't fit after `AB` and moves to the second line
+        assert_eq!(pos(d).y, 50. + 6. * 2.);
+        let after_d = DEFAULT_FONT.get_char('D').unwrap().width + 2.;
+        assert_eq!(pos(caret), pos(d) + Vec2::new(after_d * 2., 0.));
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+pub struct FixedTimestep {
+    step: f32,
+    max_steps: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(tick_rate: u32, max_steps: u32) -> Self {
+        Self {
+            step: (tick_rate as f32).recip(),
+            max_steps,
+            accumulator: 0.,
+        }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Adds `elapsed` to the accumulator and returns how many fixed steps should be simulated.
+    /// Anything beyond `max_steps` is dropped so a long stall doesn't turn into a spiral of death.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed.as_secs_f32();
+        let steps = (self.accumulator / self.step) as u32;
+        if steps > self.max_steps {
+            self.accumulator %= self.step;
+            self.max_steps
+        } else {
+            self.accumulator -= steps as f32 * self.step;
+            steps
+        }
+    }
+
+    /// How far between the last and the next tick we are, in `0..1`.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0., 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an eighth of a second per tick, so the sums stay exact
+    fn timestep(max_steps: u32) -> FixedTimestep {
+        FixedTimestep::new(8, max_steps)
+    }
+
+    #[test]
+    fn small_frames_add_up_to_a_step() {
+        let mut timestep = timestep(5);
+        let frame = Duration::from_secs_f32(1. / 32.);
+        for _ in 0..3 {
+            assert_eq!(timestep.advance(frame), 0);
+        }
+        assert_eq!(timestep.alpha(), 0.75);
+        assert_eq!(timestep.advance(frame), 1);
+        assert_eq!(timestep.alpha(), 0.);
+    }
+
+    #[test]
+    fn long_stalls_are_clamped() {
+        let mut timestep = timestep(3);
+        // 8.5 steps, only 3 of them are simulated and the half step is kept
+        assert_eq!(timestep.advance(Duration::from_secs_f32(1.0625)), 3);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(Duration::from_secs_f32(0.0625)), 1);
+        assert_eq!(timestep.alpha(), 0.);
+    }
+
+    #[test]
+    fn alpha_stays_below_one() {
+        let mut timestep = FixedTimestep::new(60, 5);
+        for ms in (1..200).map(|i| i * 7 % 53) {
+            timestep.advance(Duration::from_millis(ms));
+            let alpha = timestep.alpha();
+            assert!((0. ..1.).contains(&alpha), "{} after {} ms", alpha, ms);
+        }
+    }
+}
@@ -1,9 +1,8 @@
-use crate::res::ASTEROID_DESTROY;
+use crate::res::Sounds;
 use crate::utils::{get_color_from_resource_type, hit, HitType};
 use engine::audio::{self, AudioEngine, AudioPlayer};
 use engine::physics::PhysicsEngine;
 use engine::{physics::PhysicsModule, RenderLiteral};
-use rand::thread_rng;
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
 use std::{cell::RefCell, f32::consts::PI, rc::Rc};
@@ -23,18 +22,21 @@ pub struct Asteroid {
     pub max_health: f32,
     pub health: f32,
     audio: AudioPlayer,
+    sounds: Sounds,
 }
 impl Asteroid {
     pub fn new(
         physics_engine: &mut PhysicsEngine<HitType>,
         postion: Vec2,
         audio: AudioPlayer,
+        sounds: Sounds,
+        rng: &mut impl Rng,
     ) -> Self {
         let mut last = 0.;
         let mut points = Vec::new();
         while last < 2. * PI {
-            points.push((rand::thread_rng().gen_range(20.0..100.), last));
-            last += rand::thread_rng().gen_range(0.1..(2. * PI / MIN_VERTICES));
+            points.push((rng.gen_range(20.0..100.), last));
+            last += rng.gen_range(0.1..(2. * PI / MIN_VERTICES));
         }
         let physics_module: Rc<RefCell<PhysicsModule<HitType>>> = physics_engine.new_module(
             engine::ShapeLiteral::Polygon {
@@ -48,9 +50,11 @@ impl Asteroid {
             HitType::Asteroid {
                 dmg: 100.,
                 dmg_taken: 0.,
+                pos: postion,
                 audio: audio.clone(),
+                sounds,
             },
-            rand::thread_rng().gen_range(100. ..2000.),
+            rng.gen_range(100. ..2000.),
         );
 
         let distances: Vec<f32> = points.iter().map(|(dist, _)| *dist).collect();
@@ -61,12 +65,11 @@ impl Asteroid {
         let std_dev = 100.0_f32;
         let normal = Normal::new(mean, std_dev).unwrap();
 
-        let mut rng = thread_rng();
-        let value: f32 = normal.sample(&mut rng).clamp(-900.0_f32, 900.0_f32);
-        let value2: f32 = normal.sample(&mut rng).clamp(-900.0_f32, 900.0_f32);
+        let value: f32 = normal.sample(rng).clamp(-900.0_f32, 900.0_f32);
+        let value2: f32 = normal.sample(rng).clamp(-900.0_f32, 900.0_f32);
 
         let mut physics_module_borowed = physics_module.borrow_mut();
-        physics_module_borowed.angular_velocity = rand::thread_rng().gen_range(-1f32..1f32) * PI;
+        physics_module_borowed.angular_velocity = rng.gen_range(-1f32..1f32) * PI;
         physics_module_borowed.velocity = Vec2 {
             x: value,
             y: value2,
@@ -75,17 +78,18 @@ impl Asteroid {
         let mean2 = 50.0_f32;
         let std_dev2 = 10.0_f32;
         let normal2 = Normal::new(mean2, std_dev2).unwrap();
-        let heal: f32 = normal2.sample(&mut rng).clamp(10.0_f32, 100.0_f32);
+        let heal: f32 = normal2.sample(rng).clamp(10.0_f32, 100.0_f32);
         Self {
             physics_module,
             distances,
             angles,
-            timer: rand::thread_rng().gen_range(100f32..1000f32),
+            timer: rng.gen_range(100f32..1000f32),
             to_delete: false,
-            resorces: (rand::random(), rand::thread_rng().gen_range(10..200)),
+            resorces: (rng.gen(), rng.gen_range(10..200)),
             max_health: heal,
             health: heal,
             audio,
+            sounds,
         }
     }
 
@@ -94,39 +98,37 @@ impl Asteroid {
         if self.timer < 0. {
             self.to_delete = true
         }
-        if let HitType::Asteroid { dmg_taken, .. } = &mut self.physics_module.borrow_mut().inner {
+        let mut physics_module = self.physics_module.borrow_mut();
+        let position = physics_module.position;
+        if let HitType::Asteroid { dmg_taken, pos, .. } = &mut physics_module.inner {
+            *pos = position;
             if *dmg_taken >= 0. {
                 self.health -= *dmg_taken;
                 *dmg_taken = 0.;
             }
             if self.health <= 0. {
                 self.to_delete = true;
-                self.audio.play(ASTEROID_DESTROY.to_vec())
+                self.audio
+                    .play_sound_at(self.sounds.asteroid_destroy, position)
             }
         }
     }
 
-    pub fn polygon(&self) -> Vec<RenderLiteral> {
+    pub fn polygon(&self, alpha: f32) -> Vec<RenderLiteral> {
         let physics_module = self.physics_module.borrow();
+        let position = physics_module.interpolated_position(alpha);
+        let rotation = physics_module.interpolated_rotation(alpha);
         let mut vect: Vec<RenderLiteral> = vec![
             RenderLiteral::Game(engine::ShapeLiteral::Polygon {
-                pos: physics_module.position,
-                angles: self
-                    .angles
-                    .iter()
-                    .map(|a| a + physics_module.rotation)
-                    .collect(),
+                pos: position,
+                angles: self.angles.iter().map(|a| a + rotation).collect(),
                 distances: self.distances.clone(),
                 border_thickness: 0.,
                 colour: get_color_from_resource_type(self.resorces.0.clone()),
             }),
             RenderLiteral::Game(engine::ShapeLiteral::Polygon {
-                pos: physics_module.position,
-                angles: self
-                    .angles
-                    .iter()
-                    .map(|a| a + physics_module.rotation)
-                    .collect(),
+                pos: position,
+                angles: self.angles.iter().map(|a| a + rotation).collect(),
                 distances: self
                     .distances
                     .clone()
@@ -89,10 +89,10 @@ impl Bullet {
         }
     }
 
-    pub fn polygon(&self) -> RenderLiteral {
+    pub fn polygon(&self, alpha: f32) -> RenderLiteral {
         let physics_module = self.physics_module.borrow();
         let vect: RenderLiteral = RenderLiteral::Game(engine::ShapeLiteral::Polygon {
-            pos: physics_module.position,
+            pos: physics_module.interpolated_position(alpha),
             angles: self.angles.clone(),
             distances: self.distances.clone(),
             border_thickness: 0.,
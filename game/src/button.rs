@@ -2,7 +2,7 @@ use engine::text::TextBox;
 use engine::text::DEFAULT_FONT;
 use engine::RenderLiteral;
 use std::f32::consts::PI;
-use ultraviolet::{Vec2, Vec4};
+use ultraviolet::{Rotor2, Vec2, Vec4};
 #[derive(Clone, Debug)]
 pub struct Button<'a> {
     pub placement: Vec2,
@@ -23,17 +23,40 @@ impl<'a> Button<'a> {
         }
     }
 
+    fn angles() -> Vec<f32> {
+        vec![
+            (15. / 360.) * 2. * PI,
+            (165. / 360.) * 2. * PI,
+            (195. / 360.) * 2. * PI,
+            (345.0) / 360. * 2. * PI,
+        ]
+    }
+
+    // whether a point in UI space (anchored at the centre, like the polygon) is on the button
+    pub fn contains(&self, point: Vec2) -> bool {
+        let corners: Vec<Vec2> = Self::angles()
+            .into_iter()
+            .zip(&self.size)
+            .map(|(a, &d)| Rotor2::from_angle(a) * Vec2::unit_x() * d + self.placement)
+            .collect();
+        let mut inside = false;
+        for (i, a) in corners.iter().enumerate() {
+            let b = corners[(i + 1) % corners.len()];
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
     pub fn to_render(&self) -> Vec<RenderLiteral> {
         let mut vec = vec![RenderLiteral::UI {
             anchor: Vec2 { x: 0., y: 0. },
             shape: (engine::ShapeLiteral::Polygon {
                 pos: self.placement,
-                angles: vec![
-                    (15. / 360.) * 2. * PI,
-                    (165. / 360.) * 2. * PI,
-                    (195. / 360.) * 2. * PI,
-                    (345.0) / 360. * 2. * PI,
-                ],
+                angles: Self::angles(),
                 distances: self.size.clone(),
                 border_thickness: 0.,
                 colour: self.color,
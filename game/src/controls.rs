@@ -0,0 +1,116 @@
+use engine::config;
+use engine::input::{self, Binding, Bindings, GamepadAxis, GamepadButton};
+use winit::event::MouseButton;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+// one `Action = binding, binding` line per action, written out with the defaults if missing
+const BINDINGS_FILE: &str = "bindings.cfg";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Thrust,
+    TurnLeft,
+    TurnRight,
+    Fire,
+    // only read while aiming with the mouse, so menu clicks don't shoot
+    MouseFire,
+    OpenUpgrades,
+    Report,
+    Pause,
+    Back,
+    MenuLeft,
+    MenuRight,
+    MenuConfirm,
+    MenuClick,
+    ToggleFullscreen,
+    ToggleMouseAim,
+    Screenshot,
+}
+
+impl input::Action for Action {
+    const ALL: &'static [Self] = &[
+        Self::Thrust,
+        Self::TurnLeft,
+        Self::TurnRight,
+        Self::Fire,
+        Self::MouseFire,
+        Self::OpenUpgrades,
+        Self::Report,
+        Self::Pause,
+        Self::Back,
+        Self::MenuLeft,
+        Self::MenuRight,
+        Self::MenuConfirm,
+        Self::MenuClick,
+        Self::ToggleFullscreen,
+        Self::ToggleMouseAim,
+        Self::Screenshot,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Thrust => "Thrust",
+            Self::TurnLeft => "TurnLeft",
+            Self::TurnRight => "TurnRight",
+            Self::Fire => "Fire",
+            Self::MouseFire => "MouseFire",
+            Self::OpenUpgrades => "OpenUpgrades",
+            Self::Report => "Report",
+            Self::Pause => "Pause",
+            Self::Back => "Back",
+            Self::MenuLeft => "MenuLeft",
+            Self::MenuRight => "MenuRight",
+            Self::MenuConfirm => "MenuConfirm",
+            Self::MenuClick => "MenuClick",
+            Self::ToggleFullscreen => "ToggleFullscreen",
+            Self::ToggleMouseAim => "ToggleMouseAim",
+            Self::Screenshot => "Screenshot",
+        }
+    }
+}
+
+pub fn default_bindings() -> Bindings<Action> {
+    let axis = |axis, positive| Binding::Axis { axis, positive };
+    Bindings::new([
+        (Action::Thrust, Binding::char("w")),
+        (Action::Thrust, axis(GamepadAxis::RightTrigger, true)),
+        (Action::TurnLeft, Binding::char("a")),
+        (Action::TurnLeft, axis(GamepadAxis::LeftStickX, false)),
+        (Action::TurnRight, Binding::char("d")),
+        (Action::TurnRight, axis(GamepadAxis::LeftStickX, true)),
+        (Action::Fire, Binding::named(NamedKey::Space)),
+        (Action::Fire, Binding::Gamepad(GamepadButton::South)),
+        (Action::MouseFire, Binding::Mouse(MouseButton::Left)),
+        (Action::OpenUpgrades, Binding::char("u")),
+        (Action::OpenUpgrades, Binding::Gamepad(GamepadButton::North)),
+        (Action::Report, Binding::char("f")),
+        (Action::Report, Binding::Gamepad(GamepadButton::West)),
+        (Action::Pause, Binding::named(NamedKey::Escape)),
+        (Action::Pause, Binding::Gamepad(GamepadButton::Start)),
+        (Action::Back, Binding::named(NamedKey::Escape)),
+        (Action::Back, Binding::Gamepad(GamepadButton::East)),
+        (Action::MenuLeft, Binding::char("a")),
+        (Action::MenuLeft, Binding::Gamepad(GamepadButton::DPadLeft)),
+        (Action::MenuRight, Binding::char("d")),
+        (
+            Action::MenuRight,
+            Binding::Gamepad(GamepadButton::DPadRight),
+        ),
+        (Action::MenuConfirm, Binding::named(NamedKey::Space)),
+        (Action::MenuConfirm, Binding::Gamepad(GamepadButton::South)),
+        (Action::MenuClick, Binding::Mouse(MouseButton::Left)),
+        (Action::ToggleFullscreen, Binding::named(NamedKey::F11)),
+        (Action::ToggleMouseAim, Binding::char("m")),
+        (
+            Action::Screenshot,
+            Binding::Shortcut {
+                modifiers: ModifiersState::CONTROL,
+                key: Key::Character("s".into()),
+            },
+        ),
+    ])
+}
+
+pub fn load_bindings() -> Bindings<Action> {
+    Bindings::load_or_create(config::path(BINDINGS_FILE), default_bindings())
+}
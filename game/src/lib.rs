@@ -0,0 +1,197 @@
+use controls::{default_bindings, load_bindings, Action};
+use engine::audio::AudioEngine;
+use engine::config;
+use engine::input::{Bindings, InputState};
+use engine::recording::Recording;
+use engine::replay::{InputLog, Replay};
+use engine::scene::SceneStack;
+use engine::{
+    EngineControl, EngineInitInfo, EverythingToDraw, Game as GameTrait, Input, Lifecycle,
+    VulkanRenderer, WindowMode,
+};
+use res::{Sounds, VOLUMES_FILE};
+use run::{seed_from_env, Run, RunSeeds};
+use scenes::MainMenuScene;
+use winit::dpi::PhysicalSize;
+mod asteroid;
+mod bullet;
+mod button;
+mod controls;
+mod menu;
+mod player;
+mod res;
+mod run;
+mod scenes;
+mod upgradeManager;
+mod utils;
+
+const MAX_ZOOM_OUT: f32 = 0.000001;
+
+// `ASTEROID_INPUT_LOG=<file>` logs the session's inputs, `ASTEROID_REPLAY=<file>` plays one back,
+// e.g. to reproduce a bug report. Headless runs only replay what the test hands them.
+fn replay(engine: &EngineControl) -> Option<Replay> {
+    if let Some(log) = engine.replay_log() {
+        return Some(Replay::Play(log.clone()));
+    }
+    if engine.is_headless() {
+        return None;
+    }
+    if let Some(path) = std::env::var_os("ASTEROID_REPLAY") {
+        match InputLog::load(&path) {
+            Ok(log) => return Some(Replay::Play(log)),
+            Err(err) => log::error!("Failed to load replay: {:#}", err),
+        }
+    }
+    std::env::var_os("ASTEROID_INPUT_LOG").map(|path| Replay::Record {
+        path: path.into(),
+        seed: rand::random(),
+        seed_override: seed_from_env(),
+    })
+}
+
+// state shared by every scene
+pub struct Context {
+    run: Run,
+    audio_engine: AudioEngine,
+    sounds: Sounds,
+    engine: EngineControl,
+    window_mode: WindowMode,
+    bindings: Bindings<Action>,
+    // the ship turns towards the cursor instead of using the turn bindings
+    mouse_aim: bool,
+    viewport: PhysicalSize<u32>,
+    seeds: RunSeeds,
+    // a text field has focus, see `set_typing`
+    typing: bool,
+}
+
+impl Context {
+    // also turns on the IME for players typing in other scripts
+    fn set_typing(&mut self, typing: bool) {
+        self.typing = typing;
+        self.engine.set_text_input(typing);
+    }
+}
+
+pub struct Game {
+    ctx: Context,
+    scenes: SceneStack<Context>,
+}
+
+// for tests driving the game through a `HeadlessRunner`
+impl Game {
+    pub fn scene_depth(&self) -> usize {
+        self.scenes.depth()
+    }
+
+    /// How long the current run has been going, only advances while it isn't paused.
+    pub fn run_time(&self) -> f64 {
+        self.ctx.run.time_elapsed
+    }
+}
+
+impl GameTrait for Game {
+    fn init(engine: EngineControl) -> (EngineInitInfo, Self) {
+        engine.set_title("Asteroid Survivors");
+        // tests shouldn't need a sound card or leave a `bindings.cfg` behind
+        let headless = engine.is_headless();
+        let mut audio = if headless {
+            AudioEngine::silent()
+        } else {
+            AudioEngine::new()
+        };
+        let sounds = Sounds::load(&mut audio);
+        if !headless {
+            audio.player().load_volumes(config::path(VOLUMES_FILE));
+        }
+        let resolution = PhysicalSize {
+            width: 1280,
+            height: 720,
+        };
+        let replay = replay(&engine);
+        // a replay ignores `ASTEROID_SEED` in favour of what was set while recording
+        let mut seeds = match &replay {
+            Some(Replay::Record {
+                seed,
+                seed_override,
+                ..
+            }) => RunSeeds::new(*seed, *seed_override),
+            Some(Replay::Play(log)) => RunSeeds::new(log.seed, log.seed_override),
+            None if headless => RunSeeds::new(rand::random(), None),
+            None => RunSeeds::new(rand::random(), seed_from_env()),
+        };
+        let ctx = Context {
+            run: Run::new(audio.player(), sounds, seeds.next_run()),
+            audio_engine: audio,
+            sounds,
+            engine,
+            window_mode: WindowMode::Windowed,
+            bindings: if headless {
+                default_bindings()
+            } else {
+                load_bindings()
+            },
+            mouse_aim: false,
+            viewport: resolution,
+            seeds,
+            typing: false,
+        };
+        let scenes = SceneStack::new(Box::new(MainMenuScene::new(&ctx)));
+        (
+            EngineInitInfo {
+                windowed: true,
+                resizeable: false,
+                resolution,
+                tick_rate: 60,
+                max_catch_up_steps: 5,
+                show_profiler: false,
+                profiler_csv: std::env::var_os("ASTEROID_PROFILE_CSV").map(Into::into),
+                backend: Box::<VulkanRenderer>::default(),
+                // `ASTEROID_RECORD=<dir>` records every other frame for trailers
+                recording: std::env::var_os("ASTEROID_RECORD").map(|dir| Recording {
+                    dir: dir.into(),
+                    every_nth: 2,
+                }),
+                replay,
+            },
+            Self { ctx, scenes },
+        )
+    }
+
+    fn draw(&self, alpha: f32) -> EverythingToDraw {
+        self.scenes.draw(&self.ctx, alpha)
+    }
+
+    fn update(&mut self, dt: f32, input: &InputState) {
+        self.ctx.audio_engine.update();
+        // shortcuts need the modifiers held, so they're polled here instead of in `input`
+        if !self.ctx.typing && self.ctx.bindings.just_pressed(Action::Screenshot, input) {
+            self.ctx.engine.screenshot();
+        }
+        self.scenes.update(&mut self.ctx, dt, input);
+    }
+
+    fn input(&mut self, input: Input) {
+        // keys belong to the text field while typing
+        if !self.ctx.typing {
+            if self.ctx.bindings.released(Action::ToggleFullscreen, &input) {
+                self.ctx.window_mode = match self.ctx.window_mode {
+                    WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+                    WindowMode::BorderlessFullscreen => WindowMode::Windowed,
+                };
+                self.ctx.engine.set_window_mode(self.ctx.window_mode);
+            }
+            if self.ctx.bindings.released(Action::ToggleMouseAim, &input) {
+                self.ctx.mouse_aim = !self.ctx.mouse_aim;
+            }
+        }
+        self.scenes.input(&mut self.ctx, input);
+    }
+
+    fn lifecycle(&mut self, event: Lifecycle) {
+        if let Lifecycle::Resized(size) = event {
+            self.ctx.viewport = size;
+        }
+        self.scenes.lifecycle(&mut self.ctx, event);
+    }
+}
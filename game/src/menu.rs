@@ -1,8 +1,11 @@
 use crate::button::Button;
-use engine::{Input, RenderLiteral};
+use crate::controls::Action;
+use engine::{input::Bindings, screen_to_ui, Input, RenderLiteral};
 
 use std::f32::consts::PI;
 use ultraviolet::{Vec2, Vec4};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::TouchPhase;
 
 #[derive(Clone, Debug)]
 pub struct Menu<'a> {
@@ -10,6 +13,8 @@ pub struct Menu<'a> {
     pub selected: i8,
     pub location: Vec2,
     pub out: Option<String>,
+    // whether the cursor is over the selected button, so a click anywhere else does nothing
+    hovered: bool,
 }
 
 impl<'a> Menu<'a> {
@@ -19,6 +24,7 @@ impl<'a> Menu<'a> {
             selected: 0,
             location,
             out: None,
+            hovered: false,
         }
     }
     pub fn new_main() -> Self {
@@ -49,6 +55,7 @@ impl<'a> Menu<'a> {
             selected: 0,
             location: Vec2 { x: 0., y: 100. },
             out: None,
+            hovered: false,
         }
     }
     pub fn new_pause() -> Self {
@@ -79,6 +86,7 @@ impl<'a> Menu<'a> {
             selected: 0,
             location: Vec2 { x: 0., y: 100. },
             out: None,
+            hovered: false,
         }
     }
 
@@ -104,27 +112,53 @@ impl<'a> Menu<'a> {
         self.out.as_deref()
     }
 
-    pub fn input(&mut self, input: Input) {
-        if let Input::Keyboard { key, state } = input {
-            match (key.to_text(), state) {
-                (Some("a"), winit::event::ElementState::Released) => {
-                    self.selected = (((self.selected - 1) + 3) % 3).abs()
-                }
-                (Some("d"), winit::event::ElementState::Released) => {
-                    self.selected = ((self.selected + 1) % 3).abs()
-                }
-                (Some(" "), winit::event::ElementState::Released) => {
-                    self.out = Some(self.buttons[self.selected as usize].get_value())
-                }
-                _ => (),
-            }
+    fn hover(&mut self, pos: PhysicalPosition<f64>, viewport: PhysicalSize<u32>) {
+        let point = screen_to_ui(pos, viewport, Vec2 { x: 0., y: 0. });
+        let hovered = self.buttons.iter().position(|b| b.contains(point));
+        self.hovered = hovered.is_some();
+        if let Some(i) = hovered {
+            self.selected = i as i8;
         }
+    }
 
-        match self.selected {
-            0 => self.location = Vec2 { x: -750., y: 200. },
-            1 => self.location = Vec2 { x: 0., y: 200. },
-            2 => self.location = Vec2 { x: 750., y: 200. },
-            _ => unreachable!(),
+    pub fn input(
+        &mut self,
+        bindings: &Bindings<Action>,
+        input: Input,
+        viewport: PhysicalSize<u32>,
+    ) {
+        let count = self.buttons.len() as i8;
+        if let Input::Cursor(pos)
+        | Input::Touch {
+            pos,
+            phase: TouchPhase::Started | TouchPhase::Moved,
+            ..
+        } = input
+        {
+            self.hover(pos, viewport);
+        } else if let Input::Touch {
+            pos,
+            phase: TouchPhase::Ended,
+            ..
+        } = input
+        {
+            // a tap is a hover and a click in one
+            self.hover(pos, viewport);
+            if self.hovered {
+                self.out = Some(self.buttons[self.selected as usize].get_value())
+            }
+        } else if bindings.released(Action::MenuLeft, &input) {
+            self.selected = (self.selected - 1).rem_euclid(count);
+            self.hovered = false;
+        } else if bindings.released(Action::MenuRight, &input) {
+            self.selected = (self.selected + 1).rem_euclid(count);
+            self.hovered = false;
+        } else if bindings.released(Action::MenuConfirm, &input)
+            || (self.hovered && bindings.released(Action::MenuClick, &input))
+        {
+            self.out = Some(self.buttons[self.selected as usize].get_value())
         }
+
+        self.location = self.buttons[self.selected as usize].placement + Vec2 { x: 0., y: 200. };
     }
 }
@@ -1,13 +1,15 @@
 use crate::{
     bullet::Bullet,
-    res::SHOOT,
+    controls::Action,
+    res::Sounds,
     upgradeManager::{UpgradeType, UPGRADES},
     utils::{get_orb, hit, HitType},
 };
 use engine::{
     audio::{self, AudioEngine, AudioPlayer},
+    input::{Bindings, InputState},
     physics::{PhysicsEngine, PhysicsModule},
-    Input, RenderLiteral,
+    RenderLiteral,
 };
 use rand::Rng;
 use std::{cell::RefCell, f32::consts::PI, rc::Rc};
@@ -18,6 +20,8 @@ pub struct Player {
     pub physics_module: Rc<RefCell<PhysicsModule<HitType>>>,
     rotation_rps: f32,
     steering_keys: SteeringKeys,
+    // world position to turn towards instead of steering with `steering_keys`
+    aim: Option<Vec2>,
     shooting: Shooting,
     bullets: Vec<Bullet>,
     pub upgrades: Upgrades,
@@ -26,10 +30,15 @@ pub struct Player {
     pub shield: f32,
     pub max_shield: f32,
     audio: engine::audio::AudioPlayer,
+    sounds: Sounds,
 }
 
 impl Player {
-    pub fn new(physics_module: &mut PhysicsEngine<HitType>, audio: AudioPlayer) -> Self {
+    pub fn new(
+        physics_module: &mut PhysicsEngine<HitType>,
+        audio: AudioPlayer,
+        sounds: Sounds,
+    ) -> Self {
         let physics_module = physics_module.new_module(
             engine::ShapeLiteral::Polygon {
                 pos: Vec2::zero(),
@@ -50,10 +59,11 @@ impl Player {
             thrust: 12500.,
             rotation_rps: 1.,
             steering_keys: SteeringKeys {
-                left: false,
-                right: false,
-                forward: false,
+                left: 0.,
+                right: 0.,
+                forward: 0.,
             },
+            aim: None,
             shooting: Shooting {
                 shootnow: false,
                 cooldown: 0.5,
@@ -66,10 +76,16 @@ impl Player {
             shield: 0.,
             max_shield: 10.,
             audio,
+            sounds,
         }
     }
 
-    pub fn update(&mut self, dt: f32, physics_engine: &mut PhysicsEngine<HitType>) {
+    pub fn update(
+        &mut self,
+        dt: f32,
+        physics_engine: &mut PhysicsEngine<HitType>,
+        rng: &mut impl Rng,
+    ) {
         if self.shield < self.max_shield {
             self.shield += dt * 0.01 * self.max_shield;
         }
@@ -83,37 +99,38 @@ impl Player {
             *dmg_takenp = 0.;
         }
 
-        if self.steering_keys.forward {
+        if self.steering_keys.forward > 0. {
             let force = Rotor2::from_angle(physics_module.rotation)
                 * Vec2::unit_x()
                 * (self.thrust + self.upgrades.thrust_add)
-                * self.upgrades.thrust_mult;
+                * self.upgrades.thrust_mult
+                * self.steering_keys.forward;
             physics_module.force = force;
         }
 
-        physics_module.angular_velocity = match self.steering_keys.direction() {
-            SteeringDirection::Left => {
-                (-self.rotation_rps - self.upgrades.rotation_add)
-                    * self.upgrades.rotation_mult
-                    * 2.
-                    * PI
+        let max_angular_velocity = (self.rotation_rps + self.upgrades.rotation_add)
+            * self.upgrades.rotation_mult
+            * 2.
+            * PI;
+        let direction = match self.aim {
+            Some(target) => {
+                let to_target = target - physics_module.position;
+                let turn = (to_target.y.atan2(to_target.x) - physics_module.rotation + PI)
+                    .rem_euclid(2. * PI)
+                    - PI;
+                // slows down when close so it doesn't overshoot
+                (turn / (max_angular_velocity * dt)).clamp(-1., 1.)
             }
-            SteeringDirection::Right => {
-                (self.rotation_rps + self.upgrades.rotation_add)
-                    * self.upgrades.rotation_mult
-                    * 2.
-                    * PI
-            }
-            SteeringDirection::None => 0.,
+            None => self.steering_keys.direction(),
         };
+        physics_module.angular_velocity = max_angular_velocity * direction;
         for i in self.bullets.iter_mut() {
             i.update(dt);
         }
 
         self.bullets.retain(|a| !a.to_delete);
         if self.shooting.shootnow && self.shooting.coolingdown <= 0. {
-            self.audio.play(SHOOT.to_vec());
-            let mut rng = rand::thread_rng();
+            self.audio.play_sound(self.sounds.shoot);
             for _ in 0..self.upgrades.bullet_per_attack {
                 self.bullets.push(Bullet::new(
                     physics_engine,
@@ -134,28 +151,30 @@ impl Player {
         self.shooting.coolingdown -= dt;
     }
 
-    pub fn polygons(&self) -> Vec<RenderLiteral> {
+    pub fn polygons(&self, alpha: f32, rng: &mut impl Rng) -> Vec<RenderLiteral> {
         let physics_module = self.physics_module.borrow();
+        let position = physics_module.interpolated_position(alpha);
+        let rotation = physics_module.interpolated_rotation(alpha);
         let mut vect: Vec<RenderLiteral> =
             vec![RenderLiteral::Game(engine::ShapeLiteral::Polygon {
-                pos: physics_module.position,
+                pos: position,
                 angles: [0., 2. / 3. * PI, 4. / 3. * PI]
                     .iter()
-                    .map(|a| a + physics_module.rotation)
+                    .map(|a| a + rotation)
                     .collect(),
                 distances: vec![75., 50., 50.],
                 border_thickness: 0.,
                 colour: Vec4::new(1., 1., 1., 1.),
             })];
         for i in self.bullets.iter() {
-            vect.push(i.polygon())
+            vect.push(i.polygon(alpha))
         }
         if self.shield > 0. {
             vect.push(RenderLiteral::Game(engine::ShapeLiteral::Polygon {
-                pos: physics_module.position,
+                pos: position,
                 angles: [0., 2. / 3. * PI, 4. / 3. * PI]
                     .iter()
-                    .map(|a| a + physics_module.rotation)
+                    .map(|a| a + rotation)
                     .collect(),
                 distances: vec![75., 50., 50.]
                     .iter()
@@ -165,24 +184,21 @@ impl Player {
                 colour: Vec4::new(0., 0., 1., 1.),
             }));
         }
-        vect.push(get_orb(
-            physics_module.position,
-            self.health / self.max_health,
-            9.,
-        ));
+        vect.push(get_orb(rng, position, self.health / self.max_health, 9.));
         vect
     }
 
-    pub fn input(&mut self, input: Input) {
-        if let Input::Keyboard { key, state } = input {
-            match key.to_text() {
-                Some("w") => self.steering_keys.forward = state.is_pressed(),
-                Some("a") => self.steering_keys.left = state.is_pressed(),
-                Some("d") => self.steering_keys.right = state.is_pressed(),
-                Some(" ") => self.shooting.shootnow = state.is_pressed(),
-                _ => (),
-            }
-        }
+    // called every tick before `update`, `aim` is the cursor in world space when aiming with
+    // the mouse
+    pub fn steer(&mut self, bindings: &Bindings<Action>, input: &InputState, aim: Option<Vec2>) {
+        self.aim = aim;
+        self.steering_keys = SteeringKeys {
+            left: bindings.held_value(Action::TurnLeft, input),
+            right: bindings.held_value(Action::TurnRight, input),
+            forward: bindings.held_value(Action::Thrust, input),
+        };
+        self.shooting.shootnow = bindings.held(Action::Fire, input)
+            || aim.is_some() && bindings.held(Action::MouseFire, input);
     }
     pub fn upgrade(&mut self, value: &str) {
         let upgrade = UPGRADES[value.parse::<usize>().unwrap()].upgrade;
@@ -206,29 +222,20 @@ impl Player {
     }
 }
 
+// 0 to 1 each, keys are all or nothing while sticks and triggers go in between
 struct SteeringKeys {
-    left: bool,
-    right: bool,
-    forward: bool,
+    left: f32,
+    right: f32,
+    forward: f32,
 }
 
 impl SteeringKeys {
-    fn direction(&self) -> SteeringDirection {
-        match (self.left, self.right) {
-            (true, false) => SteeringDirection::Left,
-            (false, true) => SteeringDirection::Right,
-            _ => SteeringDirection::None,
-        }
+    // -1 is full left, 1 full right
+    fn direction(&self) -> f32 {
+        self.right - self.left
     }
 }
 
-#[derive(PartialEq)]
-enum SteeringDirection {
-    Left,
-    None,
-    Right,
-}
-
 struct Shooting {
     shootnow: bool,
     cooldown: f32,
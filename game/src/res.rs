@@ -1,4 +1,56 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use engine::audio::{AudioEngine, SoundId, SoundLimits};
+use engine::config;
+
 pub static SHOOT: &[u8] = include_bytes!("../res/shoot.mp3");
 pub static HPZS: &[u8] = include_bytes!("../res/hit_pocisku_z_asterioda.mp3");
 pub static HSZA: &[u8] = include_bytes!("../res/hit_statku_z_asteroida.mp3");
 pub static ASTEROID_DESTROY: &[u8] = include_bytes!("../res/asteroid_destory.mp3");
+
+// streamed from `music/` in the game's directory instead of being baked in, see `music_playlist`,
+// missing tracks are skipped
+pub const MENU_MUSIC: &[&str] = &["music/menu.mp3"];
+pub const RUN_MUSIC: &[&str] = &["music/run-1.mp3", "music/run-2.mp3", "music/run-3.mp3"];
+pub const LOSS_MUSIC: &[&str] = &["music/loss.mp3"];
+pub const MUSIC_FADE: Duration = Duration::from_millis(1500);
+
+pub fn music_playlist(tracks: &[&str]) -> Vec<PathBuf> {
+    tracks.iter().map(config::path).collect()
+}
+
+// `Master`, `Sfx`, `Music` and `Voice` volumes where 1 is unchanged, `muted` after one silences it
+pub const VOLUMES_FILE: &str = "volumes.cfg";
+
+// decoded once at startup instead of on every play
+#[derive(Clone, Copy, Debug)]
+pub struct Sounds {
+    pub shoot: SoundId,
+    pub bullet_hit: SoundId,
+    pub ship_hit: SoundId,
+    pub asteroid_destroy: SoundId,
+}
+
+impl Sounds {
+    pub fn load(audio: &mut AudioEngine) -> Self {
+        let sounds = Self {
+            shoot: audio.load(SHOOT).unwrap(),
+            bullet_hit: audio.load(HPZS).unwrap(),
+            ship_hit: audio.load(HSZA).unwrap(),
+            asteroid_destroy: audio.load(ASTEROID_DESTROY).unwrap(),
+        };
+        // multishot with pierce lands dozens of bullet hits a frame, one is enough to hear
+        let limit = |max_voices, priority, min_interval_ms| SoundLimits {
+            max_voices,
+            priority,
+            min_interval: Duration::from_millis(min_interval_ms),
+        };
+        audio.set_limits(sounds.shoot, limit(4, 0, 30));
+        audio.set_limits(sounds.bullet_hit, limit(4, 0, 40));
+        audio.set_limits(sounds.asteroid_destroy, limit(6, 1, 20));
+        // getting hit should always be heard
+        audio.set_limits(sounds.ship_hit, limit(2, 2, 0));
+        sounds
+    }
+}
@@ -0,0 +1,201 @@
+use crate::asteroid::Asteroid;
+use crate::player::Player;
+use crate::res::Sounds;
+use crate::upgradeManager::UpgradeManager;
+use crate::utils::HitType;
+use crate::MAX_ZOOM_OUT;
+use engine::audio::AudioPlayer;
+use engine::physics::PhysicsEngine;
+use engine::Camera;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
+use ultraviolet::Vec2;
+use winit::dpi::PhysicalSize;
+
+// everything that lives for a single playthrough
+pub struct Run {
+    pub cam_position: Vec2,
+    pub physics: PhysicsEngine<HitType>,
+    pub player: Player,
+    pub asteroid_vec: Vec<Asteroid>,
+    pub speed: f32,
+    pub upgrade_manager: UpgradeManager<'static>,
+    pub time_elapsed: f64,
+    // the player's mouse wheel zoom, on top of zooming out with speed
+    pub zoom: f32,
+    // every gameplay roll goes through this so the seed alone determines a run, ChaCha since
+    // `StdRng` may change its algorithm between `rand` versions and break old seeds
+    pub rng: ChaCha8Rng,
+    pub seed: u64,
+    ticks: u64,
+    audio: AudioPlayer,
+    sounds: Sounds,
+}
+
+// `ASTEROID_SEED` replays a given run (or `daily` for the same run for everyone that day), read
+// once when the session starts so it can be logged with the session's inputs
+pub fn seed_from_env() -> Option<u64> {
+    match std::env::var("ASTEROID_SEED").as_deref() {
+        Ok("daily") => Some(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                / (24 * 60 * 60),
+        ),
+        Ok(seed) => match seed.parse() {
+            Ok(seed) => Some(seed),
+            Err(_) => {
+                log::warn!(
+                    "ASTEROID_SEED `{}` isn't a number, using a random seed",
+                    seed
+                );
+                None
+            }
+        },
+        Err(_) => None,
+    }
+}
+
+// where every run's seed comes from, so replaying a session replays its runs too
+pub struct RunSeeds {
+    source: ChaCha8Rng,
+    // used for every run instead, see `seed_from_env`
+    fixed: Option<u64>,
+}
+
+impl RunSeeds {
+    pub fn new(seed: u64, fixed: Option<u64>) -> Self {
+        Self {
+            source: ChaCha8Rng::seed_from_u64(seed),
+            fixed,
+        }
+    }
+
+    pub fn next_run(&mut self) -> u64 {
+        self.fixed.unwrap_or_else(|| self.source.gen())
+    }
+}
+
+impl Run {
+    pub fn new(audio: AudioPlayer, sounds: Sounds, seed: u64) -> Self {
+        let mut physics = PhysicsEngine::default();
+        Self {
+            cam_position: Vec2::new(0., 0.),
+            player: Player::new(&mut physics, audio.clone(), sounds),
+            physics,
+            asteroid_vec: vec![],
+            speed: 0.,
+            upgrade_manager: UpgradeManager::new(),
+            time_elapsed: 0.,
+            zoom: 1.,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            seed,
+            ticks: 0,
+            audio,
+            sounds,
+        }
+    }
+
+    // for purely visual randomness, which runs once per frame instead of once per tick
+    // and so must not touch `rng`
+    pub fn cosmetic_rng(&self) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(self.seed ^ self.ticks)
+    }
+
+    // zooms out the faster the player goes
+    pub fn camera_scale(&self) -> f32 {
+        (0.7 - (MAX_ZOOM_OUT / (1. + (4. + -0.008 * self.speed).exp()))) * self.zoom
+    }
+
+    // each line scrolled up zooms in by a tenth
+    pub fn zoom_by(&mut self, lines: f32) {
+        self.zoom = (self.zoom * 1.1f32.powf(lines)).clamp(0.5, 2.);
+    }
+
+    // follows the player, as of the last tick
+    pub fn camera(&self, viewport: PhysicalSize<u32>) -> Camera {
+        Camera {
+            pos: self.player.physics_module.borrow().position,
+            scale: self.camera_scale(),
+            viewport,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time_elapsed += dt as f64;
+        self.ticks += 1;
+        let player_physics = self.player.physics_module.borrow();
+        self.cam_position = player_physics.position;
+        self.speed = player_physics.velocity.mag();
+        drop(player_physics);
+        self.audio.set_listener(self.cam_position);
+
+        self.player.update(dt, &mut self.physics, &mut self.rng);
+
+        self.physics.update(dt);
+        if self.rng.gen::<f64>() < 1. / 100. * ((self.time_elapsed / 1000.).floor() + 1.) {
+            let x = [1500.0f32, -1500.0f32];
+            self.asteroid_vec.push(Asteroid::new(
+                &mut self.physics,
+                self.cam_position
+                    + Vec2::new(
+                        *x.choose(&mut self.rng).unwrap(),
+                        *x.choose(&mut self.rng).unwrap(),
+                    ),
+                self.audio.clone(),
+                self.sounds,
+                &mut self.rng,
+            ));
+        }
+        for asteroid in self.asteroid_vec.iter_mut() {
+            asteroid.update(dt);
+            if asteroid.to_delete && asteroid.timer > 0.0 {
+                self.upgrade_manager
+                    .add_resource(asteroid.resorces.0, asteroid.resorces.1)
+            }
+        }
+
+        self.asteroid_vec.retain(|a| !a.to_delete);
+    }
+
+    pub fn score(&self) -> f64 {
+        self.upgrade_manager.resources.values().sum::<i32>() as f64 * self.time_elapsed
+    }
+
+    pub fn report(&self) -> String {
+        let health_ratio = self.player.health / self.player.max_health;
+        let shield_ratio = self.player.shield / self.player.max_shield;
+        let upgrade_count = self.upgrade_manager.count_possible_upgrades();
+
+        // Custom status message based on health
+        let health_message = if health_ratio > 0.75 {
+            "Ship in excellent condition."
+        } else if health_ratio > 0.5 {
+            "Hull holding up, but take care."
+        } else if health_ratio > 0.25 {
+            "Warning: Structural integrity compromised."
+        } else {
+            "Critical alert! Immediate repair required!"
+        };
+
+        // Custom message based on upgrade opportunities
+        let upgrade_message = match upgrade_count {
+            0 => "No upgrades currently affordable.",
+            1..=2 => "A couple of upgrades are within reach.",
+            3..=5 => "Multiple upgrades available — consider improving our ship.",
+            _ => "Upgrade frenzy! We can afford a full overhaul!",
+        };
+
+        format!(
+            "Good day to hunt, Captain!\n{}\n{}\nhull integrity at:  {:.1}% \nshields at:  {:.1}%\nsurvival time: {:.1} s",
+            health_message,
+            upgrade_message,
+            health_ratio * 100.,
+            shield_ratio * 100.,
+            self.time_elapsed,
+        )
+    }
+}
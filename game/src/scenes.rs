@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use engine::input::InputState;
+use engine::scene::{Scene, SceneAction};
+use engine::text::{TextBox, TextField, DEFAULT_FONT};
+use engine::{EverythingToDraw, Input, Lifecycle, RenderLiteral};
+use espeaker::Speaker;
+use ultraviolet::{Vec2, Vec4};
+
+use crate::controls::Action;
+use crate::menu::Menu;
+use crate::res::{music_playlist, LOSS_MUSIC, MENU_MUSIC, MUSIC_FADE, RUN_MUSIC};
+use crate::run::Run;
+use crate::utils::get_ui_orb;
+use crate::{Context, MAX_ZOOM_OUT};
+
+fn frame(ctx: &Context, scale: f32, shapes: Vec<RenderLiteral>) -> EverythingToDraw {
+    EverythingToDraw {
+        scale,
+        camera_pos: ctx.run.cam_position,
+        inverted: false,
+        shapes,
+    }
+}
+
+fn menu_scale(ctx: &Context) -> f32 {
+    1. - (MAX_ZOOM_OUT / (1. + (4. + -0.008 * ctx.run.speed).exp()))
+}
+
+// crossfades to `playlist` when switching between the menu, a run and the loss screen
+fn play_music(ctx: &Context, playlist: &[&str]) {
+    ctx.audio_engine
+        .player()
+        .play_music(music_playlist(playlist), MUSIC_FADE);
+}
+
+fn status_text(string: &str) -> Vec<RenderLiteral> {
+    TextBox {
+        pos: Vec2 { x: 0., y: 0. },
+        font_size: 10.,
+        string,
+        space_width: 2.,
+        ui_anchor: Some(Vec2 { x: -0.3, y: -0.5 }),
+        char_set: &DEFAULT_FONT,
+        line_gap: 5.,
+        width: 10000.,
+        colour: Vec4::one(),
+    }
+    .laid_out()
+}
+
+pub struct MainMenuScene {
+    menu: Menu<'static>,
+}
+
+impl MainMenuScene {
+    pub fn new(ctx: &Context) -> Self {
+        play_music(ctx, MENU_MUSIC);
+        Self {
+            menu: Menu::new_main(),
+        }
+    }
+}
+
+impl Scene<Context> for MainMenuScene {
+    fn input(&mut self, ctx: &mut Context, input: Input) -> SceneAction<Context> {
+        self.menu.input(&ctx.bindings, input, ctx.viewport);
+        match self.menu.out.take().as_deref() {
+            Some("exit") => ctx.engine.exit(),
+            Some("start") => {
+                ctx.run = Run::new(ctx.audio_engine.player(), ctx.sounds, ctx.seeds.next_run());
+                play_music(ctx, RUN_MUSIC);
+                return SceneAction::Replace(Box::new(RunningScene));
+            }
+            Some("high scores") => {
+                let body = reqwest::blocking::get("https://alebla.pythonanywhere.com/high")
+                    .unwrap()
+                    .text();
+                return SceneAction::Push(Box::new(HighScoreScene {
+                    high_scores: body.unwrap(),
+                }));
+            }
+            _ => (),
+        }
+        SceneAction::None
+    }
+
+    fn draw(&self, ctx: &Context, _alpha: f32) -> EverythingToDraw {
+        frame(ctx, menu_scale(ctx), self.menu.to_render())
+    }
+}
+
+pub struct RunningScene;
+
+impl Scene<Context> for RunningScene {
+    fn update(&mut self, ctx: &mut Context, dt: f32, input: &InputState) -> SceneAction<Context> {
+        let aim = input
+            .cursor()
+            .filter(|_| ctx.mouse_aim)
+            .map(|cursor| ctx.run.camera(ctx.viewport).screen_to_world(cursor));
+        ctx.run.player.steer(&ctx.bindings, input, aim);
+        ctx.run.zoom_by(input.wheel().y);
+        ctx.run.update(dt);
+        if ctx.run.player.health <= 0. {
+            return SceneAction::Replace(Box::new(LossScene::new(ctx)));
+        }
+        SceneAction::None
+    }
+
+    fn input(&mut self, ctx: &mut Context, input: Input) -> SceneAction<Context> {
+        let bindings = &ctx.bindings;
+        if bindings.released(Action::Pause, &input) {
+            SceneAction::Push(Box::new(PauseScene {
+                menu: Menu::new_pause(),
+            }))
+        } else if bindings.released(Action::OpenUpgrades, &input) {
+            ctx.run.upgrade_manager.make_menu(&mut ctx.run.rng);
+            SceneAction::Push(Box::new(UpgradeScene))
+        } else if bindings.released(Action::Report, &input) {
+            ctx.audio_engine
+                .player()
+                .play_speaker(Speaker::new().speak(&ctx.run.report()));
+            SceneAction::Push(Box::new(ReportScene))
+        } else {
+            SceneAction::None
+        }
+    }
+
+    // pause when the player tabs out so they don't come back to a wreck
+    fn lifecycle(&mut self, _ctx: &mut Context, event: Lifecycle) -> SceneAction<Context> {
+        match event {
+            Lifecycle::FocusLost | Lifecycle::Minimised => {
+                SceneAction::Push(Box::new(PauseScene {
+                    menu: Menu::new_pause(),
+                }))
+            }
+            _ => SceneAction::None,
+        }
+    }
+
+    fn draw(&self, ctx: &Context, alpha: f32) -> EverythingToDraw {
+        let mut shapes = vec![];
+        shapes.append(&mut ctx.run.player.polygons(alpha, &mut ctx.run.cosmetic_rng()));
+        shapes.extend(ctx.run.asteroid_vec.iter().flat_map(|a| a.polygon(alpha)));
+
+        EverythingToDraw {
+            scale: ctx.run.camera_scale(),
+            camera_pos: ctx
+                .run
+                .player
+                .physics_module
+                .borrow()
+                .interpolated_position(alpha),
+            inverted: false,
+            shapes,
+        }
+    }
+}
+
+pub struct PauseScene {
+    menu: Menu<'static>,
+}
+
+impl Scene<Context> for PauseScene {
+    fn input(&mut self, ctx: &mut Context, input: Input) -> SceneAction<Context> {
+        if ctx.bindings.released(Action::Pause, &input) {
+            return SceneAction::Pop;
+        }
+        self.menu.input(&ctx.bindings, input, ctx.viewport);
+        match self.menu.out.take().as_deref() {
+            Some("unpause") => SceneAction::Pop,
+            Some("menu") => SceneAction::Reset(Box::new(MainMenuScene::new(ctx))),
+            Some("desktop") => {
+                ctx.engine.exit();
+                SceneAction::None
+            }
+            _ => SceneAction::None,
+        }
+    }
+
+    fn draw(&self, ctx: &Context, _alpha: f32) -> EverythingToDraw {
+        frame(ctx, menu_scale(ctx), self.menu.to_render())
+    }
+
+    fn draws_below(&self) -> bool {
+        true
+    }
+}
+
+pub struct UpgradeScene;
+
+impl Scene<Context> for UpgradeScene {
+    fn input(&mut self, ctx: &mut Context, input: Input) -> SceneAction<Context> {
+        if ctx.bindings.released(Action::OpenUpgrades, &input) {
+            return SceneAction::Pop;
+        }
+        ctx.run
+            .upgrade_manager
+            .input(&ctx.bindings, input, ctx.viewport);
+        if let Some(a) = ctx.run.upgrade_manager.get_out() {
+            ctx.run.player.upgrade(a);
+            return SceneAction::Pop;
+        }
+        SceneAction::None
+    }
+
+    fn draw(&self, ctx: &Context, _alpha: f32) -> EverythingToDraw {
+        frame(ctx, 1., ctx.run.upgrade_manager.clone().to_render())
+    }
+
+    fn draws_below(&self) -> bool {
+        true
+    }
+}
+
+pub struct ReportScene;
+
+impl Scene<Context> for ReportScene {
+    fn input(&mut self, ctx: &mut Context, input: Input) -> SceneAction<Context> {
+        if ctx.bindings.released(Action::Report, &input) {
+            SceneAction::Pop
+        } else {
+            SceneAction::None
+        }
+    }
+
+    fn draw(&self, ctx: &Context, _alpha: f32) -> EverythingToDraw {
+        let player = &ctx.run.player;
+        let mut rng = ctx.run.cosmetic_rng();
+        let mut shapes = vec![
+            get_ui_orb(
+                &mut rng,
+                Vec2 { x: -0.5, y: -0.5 },
+                player.health / player.max_health,
+                30.,
+            ),
+            get_ui_orb(
+                &mut rng,
+                Vec2 { x: -0.5, y: -0.5 },
+                player.shield / player.max_shield,
+                30.,
+            ),
+        ];
+        shapes.append(&mut status_text(&ctx.run.report()));
+        frame(ctx, 1., shapes)
+    }
+
+    fn draws_below(&self) -> bool {
+        true
+    }
+}
+
+pub struct LossScene {
+    name: TextField,
+}
+
+impl LossScene {
+    pub fn new(ctx: &mut Context) -> Self {
+        ctx.set_typing(true);
+        play_music(ctx, LOSS_MUSIC);
+        let mut name = TextField::new(&DEFAULT_FONT, 20);
+        name.input(&Input::Text(whoami::realname()));
+        Self { name }
+    }
+}
+
+impl Scene<Context> for LossScene {
+    fn input(&mut self, ctx: &mut Context, input: Input) -> SceneAction<Context> {
+        if let Some(name) = self.name.input(&input) {
+            let client = reqwest::blocking::Client::new();
+            let mut params = HashMap::new();
+            params.insert("usr", name);
+            params.insert("score", format!("{}", ctx.run.score()));
+
+            let _ = client
+                .post("https://alebla.pythonanywhere.com/highadd")
+                .form(&params)
+                .send();
+        } else if !ctx.bindings.released(Action::Back, &input) {
+            return SceneAction::None;
+        }
+        ctx.set_typing(false);
+        SceneAction::Reset(Box::new(MainMenuScene::new(ctx)))
+    }
+
+    fn draw(&self, ctx: &Context, _alpha: f32) -> EverythingToDraw {
+        let mut shapes = vec![get_ui_orb(
+            &mut ctx.run.cosmetic_rng(),
+            Vec2 { x: -0.5, y: -0.5 },
+            ctx.run.player.health / ctx.run.player.max_health,
+            30.,
+        )];
+        shapes.append(&mut status_text(&format!(
+            "You have lost the game \n type your name and press enter to submit your score, or esc to skip \n your score is \n {:#?} \n seed {} ",
+            ctx.run.score(),
+            ctx.run.seed,
+        )));
+        shapes.append(&mut self.name.laid_out(TextBox {
+            pos: Vec2 { x: 0., y: 600. },
+            font_size: 10.,
+            string: "",
+            space_width: 2.,
+            ui_anchor: Some(Vec2 { x: -0.3, y: -0.5 }),
+            char_set: &DEFAULT_FONT,
+            line_gap: 5.,
+            width: 10000.,
+            colour: Vec4::one(),
+        }));
+        frame(ctx, 1., shapes)
+    }
+}
+
+pub struct HighScoreScene {
+    high_scores: String,
+}
+
+impl Scene<Context> for HighScoreScene {
+    fn input(&mut self, ctx: &mut Context, input: Input) -> SceneAction<Context> {
+        if ctx.bindings.released(Action::Back, &input) {
+            SceneAction::Pop
+        } else {
+            SceneAction::None
+        }
+    }
+
+    fn draw(&self, ctx: &Context, _alpha: f32) -> EverythingToDraw {
+        let mut shapes = vec![get_ui_orb(
+            &mut ctx.run.cosmetic_rng(),
+            Vec2 { x: -0.5, y: -0.5 },
+            100.,
+            30.,
+        )];
+        shapes.append(&mut status_text(&self.high_scores));
+        frame(ctx, 1., shapes)
+    }
+}
@@ -1,7 +1,8 @@
 use crate::button::Button;
+use crate::controls::Action;
 use crate::menu::Menu;
 use crate::utils::get_color_from_resource_type;
-use engine::{Input, RenderLiteral};
+use engine::{input::Bindings, Input, RenderLiteral};
 use std::collections::HashMap;
 
 // 0.7.2
@@ -12,6 +13,7 @@ use rand::{
 };
 
 use ultraviolet::{Vec2, Vec4};
+use winit::dpi::PhysicalSize;
 
 #[derive(Clone)]
 pub struct UpgradeManager<'a> {
@@ -43,8 +45,13 @@ impl UpgradeManager<'_> {
     pub fn to_render(self) -> Vec<RenderLiteral> {
         self.menu.unwrap().to_render()
     }
-    pub fn input(&mut self, input: Input) {
-        self.menu.as_mut().unwrap().input(input);
+    pub fn input(
+        &mut self,
+        bindings: &Bindings<Action>,
+        input: Input,
+        viewport: PhysicalSize<u32>,
+    ) {
+        self.menu.as_mut().unwrap().input(bindings, input, viewport);
     }
     pub fn get_out(&mut self) -> Option<&str> {
         let a = self.menu.as_mut().and_then(|menu| menu.get_out());
@@ -69,7 +76,7 @@ impl UpgradeManager<'_> {
         count
     }
 
-    pub fn make_menu(&mut self) {
+    pub fn make_menu(&mut self, rngesus: &mut impl Rng) {
         let mut possibe_upgrades: Vec<(&Upgrade, usize)> = vec![];
         let mut i: usize = 1;
         for upgrade in &UPGRADES[1..] {
@@ -82,17 +89,16 @@ impl UpgradeManager<'_> {
             possibe_upgrades.push((&UPGRADES[0], 0 as usize));
         }
 
-        let mut rngesus = rand::thread_rng();
         use rand::seq::SliceRandom;
 
         // Get up to 3 unique upgrades (or fewer if not enough exist)
         let upgrades: Vec<_> = if possibe_upgrades.len() >= 3 {
             // Choose 3 distinct upgrades randomly
-            possibe_upgrades.choose_multiple(&mut rngesus, 3).collect()
+            possibe_upgrades.choose_multiple(rngesus, 3).collect()
         } else {
             // If fewer than 3 exist, just take what's available (or return None)
             possibe_upgrades
-                .choose_multiple(&mut rngesus, possibe_upgrades.len())
+                .choose_multiple(rngesus, possibe_upgrades.len())
                 .collect()
         };
 
@@ -1,4 +1,4 @@
-use crate::res::{HPZS, HSZA};
+use crate::res::Sounds;
 use crate::upgradeManager::ResourceType;
 use engine::audio::{self, AudioEngine, AudioPlayer};
 use engine::{
@@ -10,24 +10,22 @@ use rand::Rng;
 use std::{cell::RefCell, f32::consts::PI, rc::Rc};
 use ultraviolet::{Vec2, Vec4};
 
-pub fn get_orb(pos: Vec2, hp_precent: f32, size: f32) -> RenderLiteral {
-    let mut rng = rand::thread_rng();
+pub fn get_orb(rng: &mut impl Rng, pos: Vec2, hp_precent: f32, size: f32) -> RenderLiteral {
     return RenderLiteral::Game(ShapeLiteral::Polygon {
         pos: pos,
         angles: (0..20).map(|_| rng.gen_range(0f32..2f32) * PI).collect(),
         distances: (0..20).map(|_| rng.gen_range(0f32..size) * PI).collect(),
         border_thickness: 1.,
         colour: Vec4::new(
-            rand::thread_rng().gen_range(0f32..1f32),
-            rand::thread_rng().gen_range(0f32..1f32),
-            rand::thread_rng().gen_range(0f32..1f32),
+            rng.gen_range(0f32..1f32),
+            rng.gen_range(0f32..1f32),
+            rng.gen_range(0f32..1f32),
             hp_precent,
         ),
     });
 }
 
-pub fn get_ui_orb(pos: Vec2, hp_precent: f32, size: f32) -> RenderLiteral {
-    let mut rng = rand::thread_rng();
+pub fn get_ui_orb(rng: &mut impl Rng, pos: Vec2, hp_precent: f32, size: f32) -> RenderLiteral {
     return RenderLiteral::UI {
         anchor: pos,
         shape: ShapeLiteral::Polygon {
@@ -36,9 +34,9 @@ pub fn get_ui_orb(pos: Vec2, hp_precent: f32, size: f32) -> RenderLiteral {
             distances: (0..40).map(|_| rng.gen_range(0f32..size) * PI).collect(),
             border_thickness: 1.,
             colour: Vec4::new(
-                rand::thread_rng().gen_range(0f32..1f32),
-                rand::thread_rng().gen_range(0f32..1f32),
-                rand::thread_rng().gen_range(0f32..1f32),
+                rng.gen_range(0f32..1f32),
+                rng.gen_range(0f32..1f32),
+                rng.gen_range(0f32..1f32),
                 hp_precent,
             ),
         },
@@ -70,7 +68,10 @@ pub enum HitType {
     Asteroid {
         dmg: f32,
         dmg_taken: f32,
+        // kept in step with the physics module so hit sounds come from the right place
+        pos: Vec2,
         audio: AudioPlayer,
+        sounds: Sounds,
     },
     Bullet {
         dmgb: f32,
@@ -89,11 +90,15 @@ pub fn hit(one: &mut HitType, two: &HitType) -> CollisionResponse {
         }
         (
             HitType::Asteroid {
-                dmg_taken, audio, ..
+                dmg_taken,
+                pos,
+                audio,
+                sounds,
+                ..
             },
             HitType::Player { dmgp, .. },
         ) => {
-            audio.play(HSZA.to_vec());
+            audio.play_sound_at(sounds.ship_hit, *pos);
             *dmg_taken += dmgp;
             CollisionResponse::Collide
         }
@@ -108,11 +113,15 @@ pub fn hit(one: &mut HitType, two: &HitType) -> CollisionResponse {
         }
         (
             HitType::Asteroid {
-                dmg_taken, audio, ..
+                dmg_taken,
+                pos,
+                audio,
+                sounds,
+                ..
             },
             HitType::Bullet { dmgb, pierce, .. },
         ) => {
-            audio.play(HPZS.to_vec());
+            audio.play_sound_at(sounds.bullet_hit, *pos);
 
             if pierce > &mut 0 {
                 *dmg_taken += dmgb;
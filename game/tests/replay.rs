@@ -0,0 +1,52 @@
+use engine::headless::HeadlessRunner;
+use engine::replay::{InputLog, LoggedEvent};
+use engine::Input;
+use game::Game;
+use winit::event::ElementState;
+use winit::keyboard::{Key, NamedKey};
+
+fn key(key: Key, state: ElementState) -> LoggedEvent {
+    LoggedEvent::Input(Input::Keyboard { key, state })
+}
+
+// starts a run and flies around in it for a few seconds
+fn session(seed: u64) -> InputLog {
+    let w = || Key::Character("w".into());
+    InputLog {
+        seed,
+        seed_override: None,
+        events: vec![
+            (1, key(Key::Named(NamedKey::Space), ElementState::Pressed)),
+            (2, key(Key::Named(NamedKey::Space), ElementState::Released)),
+            (10, key(w(), ElementState::Pressed)),
+            (200, key(w(), ElementState::Released)),
+            (400, key(Key::Named(NamedKey::Space), ElementState::Pressed)),
+        ],
+    }
+}
+
+fn replay(log: InputLog) -> String {
+    let mut runner = HeadlessRunner::<Game>::with_replay(log);
+    let frames = runner.replay();
+    assert_eq!(frames.len(), 401);
+    format!("{:?}", frames.last().unwrap())
+}
+
+#[test]
+fn replays_are_deterministic() {
+    assert_eq!(replay(session(7)), replay(session(7)));
+    assert_ne!(
+        replay(session(7)),
+        replay(session(8)),
+        "the log's seed is used"
+    );
+}
+
+#[test]
+fn seed_override_pins_every_run() {
+    let pinned = |seed| InputLog {
+        seed_override: Some(5),
+        ..session(seed)
+    };
+    assert_eq!(replay(pinned(7)), replay(pinned(8)));
+}
@@ -0,0 +1,47 @@
+use engine::headless::HeadlessRunner;
+use engine::Input;
+use game::Game;
+use winit::event::ElementState;
+use winit::keyboard::{Key, NamedKey};
+
+const DT: f32 = 1. / 60.;
+
+fn tap(runner: &mut HeadlessRunner<Game>, key: NamedKey) {
+    for state in [ElementState::Pressed, ElementState::Released] {
+        runner.input(Input::Keyboard {
+            key: Key::Named(key),
+            state,
+        });
+    }
+    runner.step(DT);
+}
+
+#[test]
+fn menu_to_running_to_pause() {
+    let mut runner = HeadlessRunner::<Game>::new();
+    assert_eq!(runner.game().scene_depth(), 1);
+    runner.step(DT);
+    assert_eq!(
+        runner.game().run_time(),
+        0.,
+        "the menu doesn't run the game"
+    );
+
+    // "start" is selected first
+    tap(&mut runner, NamedKey::Space);
+    assert_eq!(runner.game().scene_depth(), 1);
+    let started = runner.game().run_time();
+    assert!(started > 0.);
+
+    tap(&mut runner, NamedKey::Escape);
+    assert_eq!(runner.game().scene_depth(), 2);
+    let paused = runner.game().run_time();
+    for _ in 0..10 {
+        runner.step(DT);
+    }
+    assert_eq!(runner.game().run_time(), paused, "pausing stops the run");
+
+    tap(&mut runner, NamedKey::Escape);
+    assert_eq!(runner.game().scene_depth(), 1);
+    assert!(runner.game().run_time() > paused);
+}